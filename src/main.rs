@@ -1,143 +1,2032 @@
+mod programs;
 mod utils;
-use std::{fs, path::Path};
+use std::{cell::RefCell, io, path::PathBuf, process};
 
 use clap::{Parser, Subcommand};
-use rocksdb::DB;
+use serde_json::{json, Value};
+use solana_sdk::{pubkey::Pubkey, signer::Signer};
+use utils::amount::{format_sol, Amount};
+use utils::cluster::{Cluster, Commitment, Explorer};
+use utils::config::{Config, ConfigKey};
+use utils::crypto::PassphraseSource;
+use utils::error::ToolkitError;
+use utils::fees::{ComputeBudget, PriorityFee};
+use utils::meta::{format_timestamp, Origin, SortKey, WalletFilter};
+use utils::output::{verbose, DataEncoding, OutputFormat, Report};
+use utils::prereq::{AuditStatus, PrereqAction, PrereqSummary};
+use utils::solana::{Outcome, SendOptions, SweepPlan, TransferSummary};
+use utils::store::{StoreKind, WalletStore};
+use utils::wallet::WalletKind;
 
 #[derive(Subcommand)]
 enum Commands {
     Keygen {
         name: String,
+        /// Generate a BIP39 seed phrase with this many words (12 or 24) and derive the key from it
+        #[arg(long, value_name = "WORDS")]
+        mnemonic: Option<usize>,
+        /// Account index n in the derivation path m/44'/501'/n'/0'
+        #[arg(long, default_value_t = 0, requires = "mnemonic")]
+        account: u32,
+        /// Optional BIP39 passphrase protecting the seed phrase
+        #[arg(long, default_value = "", requires = "mnemonic")]
+        seed_passphrase: String,
+    },
+    Recover {
+        phrase: String,
+        name: String,
+        /// Account index n in the derivation path m/44'/501'/n'/0'
+        #[arg(long, default_value_t = 0)]
+        account: u32,
+        /// Optional BIP39 passphrase protecting the seed phrase
+        #[arg(long, default_value = "")]
+        seed_passphrase: String,
+    },
+    SeedKeygen {
+        name: String,
+        /// Number of words in the generated BIP39 seed phrase (12 or 24)
+        #[arg(long, default_value_t = 12)]
+        words: usize,
+        /// Optional BIP39 passphrase protecting the seed phrase
+        #[arg(long, default_value = "")]
+        seed_passphrase: String,
+    },
+    SeedRecover {
+        phrase: String,
+        name: String,
+        /// Optional BIP39 passphrase protecting the seed phrase
+        #[arg(long, default_value = "")]
+        seed_passphrase: String,
+    },
+    Derive {
+        parent: String,
+        /// Account index to derive. Defaults to the next unused index
+        #[arg(long)]
+        index: Option<u32>,
+    },
+    Grind {
+        name: String,
+        /// Base58 prefix the pubkey must start with
+        #[arg(long, default_value = "")]
+        prefix: String,
+        /// Base58 suffix the pubkey must end with
+        #[arg(long, default_value = "")]
+        suffix: String,
+        /// Match the prefix and suffix case-insensitively
+        #[arg(long)]
+        ignore_case: bool,
+        /// Number of threads to grind with. Defaults to one per CPU core
+        #[arg(long)]
+        threads: Option<usize>,
+    },
+    ImportFile {
+        path: PathBuf,
+        name: String,
+    },
+    ImportDir {
+        dir: PathBuf,
+    },
+    ExportFile {
+        name: String,
+        path: PathBuf,
+    },
+    Remove {
+        name: String,
+    },
+    Restore {
+        name: String,
+        /// Which kind of wallet to restore when the name was trashed as several
+        #[arg(long, value_enum)]
+        kind: Option<WalletKind>,
+    },
+    Rename {
+        from: String,
+        to: String,
+    },
+    Copy {
+        from: String,
+        to: String,
+    },
+    ListTrash,
+    Purge {
+        /// Wallet to delete for good from the trash
+        #[arg(required_unless_present = "all")]
+        name: Option<String>,
+        /// Empty the whole trash
+        #[arg(long, conflicts_with = "name")]
+        all: bool,
+        /// Only purge wallets of this kind
+        #[arg(long, value_enum)]
+        kind: Option<WalletKind>,
     },
     ReadKeygen {
         name: String,
     },
-    ListWallets,
+    ListWallets {
+        /// Only list wallets carrying this tag (repeatable, all must match)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Only list wallets with this origin
+        #[arg(long, value_enum)]
+        origin: Option<Origin>,
+        /// Only list wallets whose default cluster is this moniker or URL
+        #[arg(long)]
+        cluster: Option<Cluster>,
+        /// Only list wallets whose name or description contains this text
+        #[arg(long)]
+        search: Option<String>,
+        #[arg(long, value_enum, default_value_t = SortKey::Name)]
+        sort: SortKey,
+    },
+    Label {
+        name: String,
+        /// Set the description, an empty string clears it
+        #[arg(long)]
+        description: Option<String>,
+        /// Add a tag (repeatable)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Remove a tag (repeatable)
+        #[arg(long = "untag")]
+        untags: Vec<String>,
+        /// Set the default cluster, as a moniker or URL. An empty string clears it
+        #[arg(long)]
+        cluster: Option<String>,
+    },
+    /// Track an address without its secret key
+    Watch {
+        name: String,
+        pubkey: String,
+        #[arg(long)]
+        description: Option<String>,
+    },
+    /// Manage named addresses of other people and programs
+    Contact {
+        #[command(subcommand)]
+        command: ContactCommands,
+    },
     Base58ToWallet {
         pkey_bs58: String,
         name: String,
     },
+    /// Print a wallet's secret key in base58
     WalletToBase58 {
         name: String,
+        /// Confirm that the secret key may be printed in clear
+        #[arg(long)]
+        reveal: bool,
     },
+    /// Show the SOL balance of wallets or addresses
+    Balance {
+        /// Pubkeys, or names of wallets, watch-only wallets or contacts
+        #[arg(required_unless_present = "all")]
+        addresses: Vec<String>,
+        /// Every stored wallet, watch-only ones included
+        #[arg(long, conflicts_with = "addresses")]
+        all: bool,
+    },
+    /// Show an account as stored on chain
+    Account {
+        /// Pubkey, or the name of a wallet, watch-only wallet or contact
+        address: String,
+        /// How to print the account data
+        #[arg(long, value_enum, default_value_t = DataEncoding::Hex)]
+        encoding: DataEncoding,
+    },
+    /// Request SOL from the cluster's faucet and wait until it lands
     Airdrop {
+        /// Pubkey, or the name of a wallet, watch-only wallet or contact
         name: String,
+        /// SOL like 0.5, or lamports like 500000lamports. Defaults to 2 SOL
+        #[arg(long, conflicts_with = "top_up")]
+        amount: Option<String>,
+        /// Only request what is missing for the balance to reach this amount
+        #[arg(long, value_name = "AMOUNT")]
+        top_up: Option<String>,
+        /// Retries when the faucet rate limits us, waiting twice as long each time
+        #[arg(long, default_value_t = 4)]
+        retries: u32,
     },
     Transfer {
         from: String,
+        /// Recipient pubkey, or the name of a wallet, watch-only wallet or contact
         to: String,
+        /// SOL like 0.1 or 1.5SOL, lamports like 100000lamports, or ALL
         amount: String,
     },
     CleanWallet {
         from: String,
+        /// Recipient pubkey, or the name of a wallet, watch-only wallet or contact
         to: String,
-    }, // Submit
+        /// Send token balances to the recipient's associated token accounts, creating them
+        /// when needed. Without it, token accounts must be empty to be closed
+        #[arg(long)]
+        move_tokens: bool,
+    },
+    /// Submit the WBA prerequisites to the prereq program
+    Prereq {
+        #[command(subcommand)]
+        command: PrereqCommands,
+    },
+    /// Inspect and upgrade the wallet database
+    Db {
+        #[command(subcommand)]
+        command: DbCommands,
+    },
+    /// Read and change settings of a config profile
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+    /// Switch between config profiles
+    Profile {
+        #[command(subcommand)]
+        command: ProfileCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum ContactCommands {
+    Add {
+        name: String,
+        pubkey: String,
+        #[arg(long)]
+        description: Option<String>,
+    },
+    Remove {
+        name: String,
+    },
+    List,
 }
 
+#[derive(Subcommand)]
+enum DbCommands {
+    /// Upgrade every record to the current schema version
+    Migrate {
+        /// Only report what would be upgraded
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Report corrupt, outdated or unknown entries
+    Check {
+        /// Also decrypt every secret and check it against its pubkey
+        #[arg(long)]
+        decrypt: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum PrereqCommands {
+    /// Record a GitHub handle with the prereq program, signed by the wallet
+    Submit {
+        wallet: String,
+        #[arg(long)]
+        github: String,
+    },
+    /// Change the GitHub handle recorded for the wallet
+    Update {
+        wallet: String,
+        #[arg(long)]
+        github: String,
+    },
+    /// Check whether the wallet has submitted
+    Status {
+        /// Wallet name, pubkey, watch-only wallet or contact
+        wallet: String,
+    },
+    /// Print what the prereq account of the wallet holds
+    Show {
+        /// Wallet name, pubkey, watch-only wallet or contact
+        wallet: String,
+    },
+    /// Check a cohort roster against the prereq accounts on chain
+    Audit {
+        /// CSV file of github,pubkey rows, with an optional header
+        #[arg(long)]
+        roster: PathBuf,
+    },
+    /// List every prereq account on chain, with the handles claimed more than once
+    ListAll {
+        /// Only accounts whose GitHub handle contains this, ignoring case
+        #[arg(long)]
+        github: Option<String>,
+        /// Only accounts whose GitHub handle is stored more than once
+        #[arg(long)]
+        duplicates: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Print one setting of the profile
+    Get {
+        #[arg(value_enum)]
+        key: ConfigKey,
+    },
+    /// Change one setting of the profile, creating the profile if needed. An empty value clears it
+    Set {
+        #[arg(value_enum)]
+        key: ConfigKey,
+        value: String,
+    },
+    /// Print the whole config file
+    Show,
+}
+
+#[derive(Subcommand)]
+enum ProfileCommands {
+    /// Use this profile for every command that does not pass `--profile`
+    Use { name: String },
+}
+
+const DEFAULT_DB_PATH: &str = "wba_toolkit";
+
 #[derive(Parser)]
 #[command(name = "WBA Turbin3 Toolkit CLI")]
 #[command(version = "1.0")]
 #[command(author = "kox <garsanzi@gmail.com>")]
 #[command(about = "Does awesome things with Solana")]
 struct Cli {
-    #[arg(long, default_value = "wba_toolkit", global = true)]
-    db_path: String,
+    /// Config file holding the profiles. Defaults to $XDG_CONFIG_HOME/wba_toolkit/config.toml
+    #[arg(long, env = "WBA_TOOLKIT_CONFIG", global = true)]
+    config: Option<PathBuf>,
+
+    /// Config profile to take defaults from. Defaults to the active profile
+    #[arg(long, env = "WBA_TOOLKIT_PROFILE", global = true)]
+    profile: Option<String>,
+
+    /// Where wallets are stored. Prefix with `rocksdb:`, `dir:`, `sqlite:` or `memory:` to pick the backend.
+    /// Defaults to wba_toolkit
+    #[arg(long, global = true)]
+    db_path: Option<String>,
+
+    /// Storage backend for `--db-path`. Defaults to RocksDB
+    #[arg(long, value_enum, global = true)]
+    store: Option<StoreKind>,
+
+    /// Solana cluster: devnet, testnet, mainnet-beta, localnet or an RPC URL. Defaults to the
+    /// wallet's default cluster, then devnet
+    #[arg(long, global = true)]
+    cluster_url: Option<Cluster>,
+
+    /// How settled a transaction has to be before it is reported. Defaults to finalized
+    #[arg(long, value_enum, global = true)]
+    commitment: Option<Commitment>,
+
+    /// Wallet paying transaction fees instead of the sender
+    #[arg(long, global = true)]
+    fee_payer: Option<String>,
+
+    /// Block explorer to link transactions to
+    #[arg(long, value_enum, default_value_t = Explorer::Solana, global = true)]
+    explorer: Explorer,
+
+    /// Passphrase used to encrypt and decrypt wallet secrets. Prompted for when not set.
+    #[arg(
+        long,
+        env = "WBA_TOOLKIT_PASSPHRASE",
+        hide_env_values = true,
+        global = true
+    )]
+    passphrase: Option<String>,
+
+    /// Allow overwriting an existing wallet
+    #[arg(long, global = true)]
+    force: bool,
+
+    /// Sign transactions without asking for confirmation
+    #[arg(long, short, global = true)]
+    yes: bool,
+
+    /// Simulate transactions instead of sending them: print the compute units, logs, balance
+    /// changes and fee, and broadcast nothing
+    #[arg(long, global = true)]
+    simulate: bool,
 
-    #[arg(long, default_value = "https://api.devnet.solana.com", global = true)]
-    cluster_url: String,
+    /// Priority fee in micro-lamports per compute unit, paid on top of the base fee
+    #[arg(long, global = true, conflicts_with = "auto_fee")]
+    priority_fee: Option<u64>,
+
+    /// Set the priority fee to this percentile of recent fees paid for the same accounts.
+    /// Defaults to the 75th when given without a value, as in `--auto-fee` or `--auto-fee=90`
+    #[arg(
+        long,
+        global = true,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "75",
+        value_parser = clap::value_parser!(u8).range(0..=100)
+    )]
+    auto_fee: Option<u8>,
+
+    /// Compute units a transaction may use. The priority fee is paid on this limit, not on
+    /// the units used. Defaults to the runtime's 200,000 per instruction
+    #[arg(long, global = true)]
+    compute_unit_limit: Option<u32>,
+
+    /// How to print results: text for people, json for scripts, an aligned table or csv. Defaults to text
+    #[arg(long, value_enum, global = true)]
+    output: Option<OutputFormat>,
+
+    /// Print debug information to stderr
+    #[arg(long, short, global = true)]
+    verbose: bool,
 
     #[command(subcommand)]
     command: Commands,
 }
 
+/// The wallet passphrase from `--passphrase` or `WBA_TOOLKIT_PASSPHRASE`, or else prompted
+/// for the first time a secret is opened or sealed, and kept for the rest of the command.
+struct PromptedPassphrase {
+    value: RefCell<Option<String>>,
+}
+
+impl PromptedPassphrase {
+    fn new(passphrase: Option<String>) -> Self {
+        PromptedPassphrase {
+            value: RefCell::new(passphrase),
+        }
+    }
+
+    /// The passphrase to seal new secrets with. A typed one is asked for twice: a typo
+    /// would lock the secret away for good.
+    fn confirmed(&self) -> Result<String, ToolkitError> {
+        if let Some(passphrase) = self.value.borrow().clone() {
+            return Ok(passphrase);
+        }
+
+        let passphrase = self.passphrase()?;
+        if rpassword::prompt_password("Repeat the wallet passphrase: ")? != passphrase {
+            self.value.take();
+            return Err(ToolkitError::InvalidInput(
+                "The passphrases do not match".to_string(),
+            ));
+        }
+
+        Ok(passphrase)
+    }
+}
+
+impl PassphraseSource for PromptedPassphrase {
+    fn passphrase(&self) -> Result<String, ToolkitError> {
+        if let Some(passphrase) = self.value.borrow().clone() {
+            return Ok(passphrase);
+        }
+
+        let passphrase = rpassword::prompt_password("Wallet passphrase: ")?;
+        self.value.replace(Some(passphrase.clone()));
+
+        Ok(passphrase)
+    }
+}
+
+/// Values from the selected profile stand in for the global options that were not given.
+/// `config` and `profile` commands work on the file itself and take nothing from it.
+fn apply_profile(cli: &mut Cli) -> Result<(), ToolkitError> {
+    if matches!(
+        cli.command,
+        Commands::Config { .. } | Commands::Profile { .. }
+    ) {
+        return Ok(());
+    }
+
+    let config = match cli.config.clone().or_else(Config::default_path) {
+        Some(path) => Config::load(&path)?,
+        None => Config::default(),
+    };
+    let profile = config.profile(cli.profile.as_deref())?;
+
+    cli.db_path = cli.db_path.take().or(profile.db_path);
+    cli.cluster_url = cli.cluster_url.take().or(profile.cluster);
+    cli.commitment = cli.commitment.or(profile.commitment);
+    cli.fee_payer = cli.fee_payer.take().or(profile.fee_payer);
+    cli.output = cli.output.or(profile.output);
+
+    Ok(())
+}
+
+/// `--cluster-url` or the profile's cluster wins, then the wallet's own default cluster,
+/// then devnet.
+fn resolve_cluster(
+    cluster_url: &Option<Cluster>,
+    db: &dyn WalletStore,
+    name: &str,
+) -> Result<Cluster, ToolkitError> {
+    if let Some(cluster) = cluster_url {
+        return Ok(cluster.clone());
+    }
+
+    match utils::wallet::read_wallet_meta(db, name)
+        .ok()
+        .flatten()
+        .and_then(|meta| meta.cluster)
+    {
+        Some(cluster) => cluster.parse(),
+        None => Ok(Cluster::default()),
+    }
+}
+
+/// Prints what is about to be signed to stderr and asks to go ahead, unless `--yes`.
+fn confirm(summary: &str, yes: bool) -> Result<(), ToolkitError> {
+    eprintln!("{}", summary);
+    if yes {
+        return Ok(());
+    }
+
+    eprint!("Sign and send? [y/N] ");
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => Ok(()),
+        _ => Err(ToolkitError::Cancelled("Nothing was sent".to_string())),
+    }
+}
+
+/// An airdrop amount: any amount but `ALL`, which means nothing to a faucet.
+fn faucet_lamports(amount: &str) -> Result<u64, ToolkitError> {
+    match amount.parse::<Amount>()? {
+        Amount::Lamports(lamports) => Ok(lamports),
+        Amount::All => Err(ToolkitError::InvalidInput(
+            "ALL is not an amount a faucet can send".to_string(),
+        )),
+    }
+}
+
+fn describe_transfer(summary: &TransferSummary, cluster: &Cluster) -> String {
+    [
+        format!(
+            "Transfer {} SOL ({} lamports) on {}",
+            format_sol(summary.amount),
+            summary.amount,
+            cluster
+        ),
+        format!("  from: {}", summary.from),
+        format!("  to:   {}", summary.to),
+        format!(
+            "  fee:  {} SOL ({} lamports), paid by {}",
+            format_sol(summary.fee),
+            summary.fee,
+            summary.fee_payer
+        ),
+    ]
+    .join("\n")
+}
+
+fn describe_sweep(plan: &SweepPlan, cluster: &Cluster) -> String {
+    let mut lines = vec![
+        format!(
+            "Sweep {} SOL ({} lamports) on {}",
+            format_sol(plan.amount),
+            plan.amount,
+            cluster
+        ),
+        format!("  from: {}", plan.from),
+        format!("  to:   {}", plan.to),
+    ];
+    for token in &plan.token_accounts {
+        let action = if token.amount > 0 && !token.is_native {
+            format!(
+                "move {} tokens of mint {} and close",
+                token.amount, token.mint
+            )
+        } else {
+            "close".to_string()
+        };
+        lines.push(format!("  {} token account {}", action, token.address));
+    }
+    lines.push(format!(
+        "  fee:  {} SOL ({} lamports), paid by {}",
+        format_sol(plan.fee),
+        plan.fee,
+        plan.fee_payer
+    ));
+
+    lines.join("\n")
+}
+
+fn describe_prereq(summary: &PrereqSummary, cluster: &Cluster) -> String {
+    let instruction = match summary.action {
+        PrereqAction::Submit => "complete",
+        PrereqAction::Update => "update",
+    };
+
+    [
+        format!(
+            "Prereq {} with GitHub handle {} on {}",
+            instruction, summary.github, cluster
+        ),
+        format!("  signer: {}", summary.signer),
+        format!("  prereq: {}", summary.prereq),
+        format!(
+            "  fee:    {} SOL ({} lamports), paid by {}",
+            format_sol(summary.fee),
+            summary.fee,
+            summary.fee_payer
+        ),
+    ]
+    .join("\n")
+}
+
+/// A sent transaction as its explorer link, a simulated one as what it would do.
+fn outcome_text(outcome: &Outcome, cluster: &Cluster, explorer: Explorer) -> String {
+    let simulation = match outcome {
+        Outcome::Sent(signature) => {
+            return format!(
+                "Success! Check out your TX here: {}",
+                explorer.tx_url(cluster, signature)
+            )
+        }
+        Outcome::Simulated(simulation) => simulation,
+    };
+
+    let mut lines = vec![
+        "Simulated, nothing was sent".to_string(),
+        format!(
+            "  compute units: {}",
+            simulation
+                .units_consumed
+                .map_or("unknown".to_string(), |units| units.to_string())
+        ),
+        format!(
+            "  fee: {} SOL ({} lamports)",
+            format_sol(simulation.fee),
+            simulation.fee
+        ),
+        "  balance changes:".to_string(),
+    ];
+    lines.extend(
+        simulation
+            .balance_changes
+            .iter()
+            .filter(|change| change.delta() != 0)
+            .map(|change| {
+                format!(
+                    "    {}: {} -> {} lamports ({:+})",
+                    change.pubkey,
+                    change.before,
+                    change.after,
+                    change.delta()
+                )
+            }),
+    );
+    lines.push("  logs:".to_string());
+    lines.extend(simulation.logs.iter().map(|log| format!("    {}", log)));
+
+    lines.join("\n")
+}
+
+/// Adds the `signature` and `explorer` of a sent transaction, or the `simulation` of one
+/// that was not, to a command's JSON document.
+fn with_outcome(
+    mut data: Value,
+    outcome: &Outcome,
+    cluster: &Cluster,
+    explorer: Explorer,
+) -> Value {
+    let (signature, explorer, simulation) = match outcome {
+        Outcome::Sent(signature) => (
+            json!(signature.to_string()),
+            json!(explorer.tx_url(cluster, signature)),
+            Value::Null,
+        ),
+        Outcome::Simulated(simulation) => (
+            Value::Null,
+            Value::Null,
+            json!({
+                "units_consumed": simulation.units_consumed,
+                "fee": simulation.fee,
+                "balance_changes": simulation
+                    .balance_changes
+                    .iter()
+                    .map(|change| {
+                        json!({
+                            "pubkey": change.pubkey.to_string(),
+                            "before": change.before,
+                            "after": change.after,
+                            "delta": change.delta(),
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+                "logs": simulation.logs,
+            }),
+        ),
+    };
+
+    if let Some(fields) = data.as_object_mut() {
+        fields.insert("signature".to_string(), signature);
+        fields.insert("explorer".to_string(), explorer);
+        fields.insert("simulation".to_string(), simulation);
+    }
+
+    data
+}
+
+fn read_fee_payer(
+    db: &dyn WalletStore,
+    fee_payer: &Option<String>,
+    passphrase: &PromptedPassphrase,
+) -> Result<Option<utils::wallet::Wallet>, ToolkitError> {
+    fee_payer
+        .as_deref()
+        .map(|name| utils::wallet::read_wallet(db, name, passphrase))
+        .transpose()
+}
+
 fn main() {
-    println!("welcome to main");
+    let mut cli = Cli::parse();
+    utils::output::set_verbose(cli.verbose);
+
+    verbose!("welcome to main");
+
+    let profile = apply_profile(&mut cli);
+    let output = cli.output.unwrap_or_default();
+
+    match profile.and_then(|()| run(cli)) {
+        Ok(report) => println!("{}", report.render(output)),
+        Err(e) => {
+            if output == OutputFormat::Json {
+                println!("{}", utils::output::error_document(&e));
+            } else {
+                eprintln!("Error: {}", e);
+            }
+            process::exit(e.exit_code());
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<Report, ToolkitError> {
+    let command = match cli.command {
+        Commands::Config { command } => return run_config(cli.config, cli.profile, command),
+        Commands::Profile {
+            command: ProfileCommands::Use { name },
+        } => {
+            let (path, mut config) = config_file(cli.config)?;
+            config.use_profile(&name)?;
+            config.save(&path)?;
+
+            return Ok(Report::new(
+                format!("Now using profile {}", name),
+                json!({ "active_profile": name, "path": path.display().to_string() }),
+            ));
+        }
+        command => command,
+    };
 
-    let cli = Cli::parse();
-    let path = Path::new(&cli.db_path);
     let cluster_url = cli.cluster_url;
+    let commitment = cli.commitment.unwrap_or_default();
+    let fee_payer = cli.fee_payer;
+    let explorer = cli.explorer;
+    let passphrase = PromptedPassphrase::new(cli.passphrase);
+    let force = cli.force;
+    let yes = cli.yes;
+    let simulate = cli.simulate;
+    let priority_fee = match (cli.priority_fee, cli.auto_fee) {
+        (Some(price), _) => Some(PriorityFee::Fixed(price)),
+        (None, Some(percentile)) => Some(PriorityFee::Auto { percentile }),
+        (None, None) => None,
+    };
+    let send_options = SendOptions {
+        compute_budget: ComputeBudget {
+            priority_fee,
+            unit_limit: cli.compute_unit_limit,
+        },
+        simulate,
+    };
 
-    // Create the directory if it doesn't exist
-    if !path.exists() {
-        fs::create_dir(path).expect("Failed to create db directory");
+    let db_path = cli.db_path.as_deref().unwrap_or(DEFAULT_DB_PATH);
+    let db = utils::store::open_store(db_path, cli.store)?;
+
+    // Older databases are upgraded on open, `db` commands inspect them as they are.
+    // Plaintext secrets are only encrypted by `db migrate`, never with a passphrase
+    // typed for some other command
+    if !matches!(command, Commands::Db { .. }) {
+        let report = utils::db::migrate(&db, None, false)?;
+        if let Some((key, e)) = report.failed.first() {
+            return Err(ToolkitError::Decode(format!(
+                "Database cannot be upgraded on open ({}: {}), run `db migrate`",
+                key, e
+            )));
+        }
+        if !report.upgraded.is_empty() {
+            eprintln!(
+                "Migrated {} records from schema {} to {}",
+                report.upgraded.len(),
+                report.from,
+                report.to
+            );
+        }
     }
 
-    let db = DB::open_default(&cli.db_path).unwrap();
+    let report = match command {
+        Commands::Keygen {
+            name,
+            mnemonic: Some(word_count),
+            account,
+            seed_passphrase,
+        } => {
+            let passphrase = passphrase.confirmed()?;
+            let (wallet, mnemonic) = utils::wallet::generate_mnemonic_keypair(
+                &db,
+                &name,
+                &passphrase,
+                word_count,
+                account,
+                &seed_passphrase,
+                force,
+            )?;
+            let path = format!("m/44'/501'/{}'/0'", account);
 
-    match cli.command {
-        Commands::Keygen { name } => utils::wallet::generate_keypair(&db, &name),
+            Report::new(
+                format!(
+                    "You've generated a new Solana wallet: {} ({})\n\
+                     Write down your seed phrase and keep it somewhere safe:\n\
+                     {}\n\
+                     Wallet {} saved to the wallet store",
+                    wallet.pubkey, path, mnemonic, name
+                ),
+                json!({
+                    "name": name,
+                    "pubkey": wallet.pubkey,
+                    "derivation_path": path,
+                    "seed_phrase": mnemonic.to_string(),
+                }),
+            )
+        }
+        Commands::Keygen { name, .. } => {
+            let passphrase = passphrase.confirmed()?;
+            let wallet = utils::wallet::generate_keypair(&db, &name, &passphrase, force)?;
+
+            Report::new(
+                format!(
+                    "You've generated a new Solana wallet: {}\nWallet {} saved to the wallet store",
+                    wallet.pubkey, name
+                ),
+                json!({ "name": name, "pubkey": wallet.pubkey }),
+            )
+        }
+        Commands::Recover {
+            phrase,
+            name,
+            account,
+            seed_passphrase,
+        } => {
+            let passphrase = passphrase.confirmed()?;
+            let wallet = utils::wallet::mnemonic_to_wallet(&phrase, &seed_passphrase, account)?;
+            utils::wallet::save_wallet_to_db(
+                &wallet,
+                &db,
+                &name,
+                &passphrase,
+                force,
+                Origin::Derived,
+            )?;
+
+            Report::new(
+                format!("Wallet {} recovered: {}", name, wallet.pubkey),
+                json!({ "name": name, "pubkey": wallet.pubkey }),
+            )
+        }
+        Commands::SeedKeygen {
+            name,
+            words,
+            seed_passphrase,
+        } => {
+            let passphrase = passphrase.confirmed()?;
+            let mnemonic = utils::seed::generate_seed_wallet(
+                &db,
+                &name,
+                &passphrase,
+                words,
+                &seed_passphrase,
+                force,
+            )?;
+
+            Report::new(
+                format!(
+                    "You've generated a new seed wallet: {}\n\
+                     Write down your seed phrase and keep it somewhere safe:\n\
+                     {}\n\
+                     Seed wallet {} saved to the wallet store",
+                    name, mnemonic, name
+                ),
+                json!({ "name": name, "seed_phrase": mnemonic.to_string() }),
+            )
+        }
+        Commands::SeedRecover {
+            phrase,
+            name,
+            seed_passphrase,
+        } => {
+            let passphrase = passphrase.confirmed()?;
+            utils::seed::import_seed_wallet(
+                &db,
+                &phrase,
+                &name,
+                &passphrase,
+                &seed_passphrase,
+                force,
+            )?;
+
+            Report::new(
+                format!("Seed wallet {} recovered", name),
+                json!({ "name": name }),
+            )
+        }
+        Commands::Derive { parent, index } => {
+            let (name, wallet) =
+                utils::seed::derive_child_wallet(&db, &parent, index, &passphrase)?;
+
+            Report::new(
+                format!("Derived wallet {}: {}", name, wallet.pubkey),
+                json!({ "name": name, "pubkey": wallet.pubkey }),
+            )
+        }
+        Commands::Grind {
+            name,
+            prefix,
+            suffix,
+            ignore_case,
+            threads,
+        } => {
+            let target = utils::grind::GrindTarget::new(&prefix, &suffix, ignore_case)?;
+            // Fail now rather than after a long grind
+            utils::wallet::ensure_name_available(&db, &name, force)?;
+            let passphrase = passphrase.confirmed()?;
+            utils::wallet::verify_passphrase(&db, &passphrase)?;
+
+            let threads = threads.unwrap_or_else(utils::grind::default_threads);
+            let keypair = utils::grind::grind_keypair(&target, threads);
+            let wallet = utils::wallet::Wallet {
+                pubkey: keypair.pubkey().to_string(),
+                secret_key: keypair.to_bytes().to_vec().into(),
+            };
+
+            utils::wallet::save_wallet_to_db(
+                &wallet,
+                &db,
+                &name,
+                &passphrase,
+                force,
+                Origin::Generated,
+            )?;
+
+            Report::new(
+                format!(
+                    "Wallet {} saved to the wallet store: {}",
+                    name, wallet.pubkey
+                ),
+                json!({ "name": name, "pubkey": wallet.pubkey }),
+            )
+        }
+        Commands::ImportFile { path, name } => {
+            let passphrase = passphrase.confirmed()?;
+            let wallet = utils::keyfile::import_wallet_file(&db, &path, &name, &passphrase, force)?;
+
+            Report::new(
+                format!("Wallet {} imported: {}", name, wallet.pubkey),
+                json!({ "name": name, "pubkey": wallet.pubkey, "path": path.display().to_string() }),
+            )
+        }
+        Commands::ImportDir { dir } => {
+            let mut lines = Vec::new();
+            let mut imported = Vec::new();
+            let mut skipped = Vec::new();
+            let passphrase = passphrase.confirmed()?;
+
+            for (name, result) in utils::keyfile::import_wallet_dir(&db, &dir, &passphrase, force)?
+            {
+                match result {
+                    Ok(wallet) => {
+                        lines.push(format!("Wallet {} imported: {}", name, wallet.pubkey));
+                        imported.push(json!({ "name": name, "pubkey": wallet.pubkey }));
+                    }
+                    Err(e) => {
+                        lines.push(format!("Skipped {}: {}", name, e));
+                        skipped.push(json!({ "name": name, "error": e.to_string() }));
+                    }
+                }
+            }
+
+            Report::new(
+                lines.join("\n"),
+                json!({ "imported": imported, "skipped": skipped }),
+            )
+        }
+        Commands::ExportFile { name, path } => {
+            let wallet = utils::wallet::read_wallet(&db, &name, &passphrase)?;
+            utils::keyfile::write_wallet_to_file(&wallet, &path)?;
+
+            Report::new(
+                format!("Wallet {} exported to {}", name, path.display()),
+                json!({ "name": name, "pubkey": wallet.pubkey, "path": path.display().to_string() }),
+            )
+        }
+        Commands::Remove { name } => {
+            utils::wallet::remove_wallet(&db, &name)?;
+
+            Report::new(
+                format!("Wallet {} moved to the trash", name),
+                json!({ "name": name }),
+            )
+        }
+        Commands::Restore { name, kind } => {
+            utils::wallet::restore_wallet(&db, &name, kind, force)?;
+
+            Report::new(format!("Wallet {} restored", name), json!({ "name": name }))
+        }
+        Commands::Rename { from, to } => {
+            utils::wallet::rename_wallet(&db, &from, &to, force)?;
+
+            Report::new(
+                format!("Wallet {} renamed to {}", from, to),
+                json!({ "from": from, "to": to }),
+            )
+        }
+        Commands::Copy { from, to } => {
+            utils::wallet::copy_wallet(&db, &from, &to, force)?;
+
+            Report::new(
+                format!("Wallet {} copied to {}", from, to),
+                json!({ "from": from, "to": to }),
+            )
+        }
+        Commands::ListTrash => {
+            let trashed = utils::wallet::list_trash(&db)?;
+            let text = if trashed.is_empty() {
+                "The trash is empty".to_string()
+            } else {
+                trashed
+                    .iter()
+                    .map(|trashed| {
+                        format!(
+                            "{} kind={} removed={}",
+                            trashed.name,
+                            trashed.kind.label(),
+                            format_timestamp(trashed.removed_at)
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+            let trash: Vec<_> = trashed
+                .iter()
+                .map(|trashed| {
+                    json!({
+                        "name": trashed.name,
+                        "kind": trashed.kind.label(),
+                        "removed": format_timestamp(trashed.removed_at),
+                    })
+                })
+                .collect();
+
+            Report::new(text, json!({ "trash": trash }))
+        }
+        Commands::Purge { name, kind, .. } => {
+            let purged: Vec<String> = utils::wallet::purge_trash(&db, name.as_deref(), kind)?
+                .into_iter()
+                .map(|trashed| format!("{} ({})", trashed.name, trashed.kind.label()))
+                .collect();
+
+            Report::new(
+                format!("Purged: {}", purged.join(", ")),
+                json!({ "purged": purged }),
+            )
+        }
         Commands::ReadKeygen { name } => {
-            let wallet = utils::wallet::read_wallet(&db, &name);
-            println!("Wallet read: {:?}", wallet.pubkey);
+            let wallet = utils::wallet::read_wallet(&db, &name, &passphrase)?;
+
+            Report::new(
+                format!("Wallet {}: {}", name, wallet.pubkey),
+                json!({ "name": name, "pubkey": wallet.pubkey }),
+            )
         }
-        Commands::ListWallets => {
-            let wallets = utils::wallet::list_wallets(&db);
-            println!("wallets: {:?}", wallets)
+        Commands::ListWallets {
+            tags,
+            origin,
+            cluster,
+            search,
+            sort,
+        } => {
+            let filter = WalletFilter {
+                tags,
+                origin,
+                cluster,
+                search,
+            };
+            let entries = utils::wallet::list_wallet_entries(&db, &passphrase, &filter, sort)?;
+
+            let lines: Vec<String> = entries
+                .iter()
+                .map(|entry| {
+                    let tags = entry.meta.tags.iter().cloned().collect::<Vec<_>>();
+                    format!(
+                        "{} {} origin={:?} created={} tags=[{}] cluster={}{} {}",
+                        entry.kind.record_key(&entry.name),
+                        entry.pubkey.as_deref().unwrap_or("-"),
+                        entry.meta.origin,
+                        format_timestamp(entry.meta.created_at),
+                        tags.join(","),
+                        entry.meta.cluster.as_deref().unwrap_or("-"),
+                        entry
+                            .meta
+                            .swept_at
+                            .map(|swept_at| format!(" swept={}", format_timestamp(swept_at)))
+                            .unwrap_or_default(),
+                        entry.meta.description.as_deref().unwrap_or("")
+                    )
+                })
+                .collect();
+            let wallets: Vec<_> = entries
+                .iter()
+                .map(|entry| {
+                    json!({
+                        "name": entry.name,
+                        "kind": entry.kind.label(),
+                        "pubkey": entry.pubkey,
+                        "origin": entry.meta.origin,
+                        "created": format_timestamp(entry.meta.created_at),
+                        "tags": entry.meta.tags,
+                        "cluster": entry.meta.cluster,
+                        "swept": entry.meta.swept_at.map(format_timestamp),
+                        "description": entry.meta.description,
+                    })
+                })
+                .collect();
+
+            Report::new(lines.join("\n"), json!({ "wallets": wallets }))
         }
-        Commands::Base58ToWallet { pkey_bs58, name } => {
-            match utils::wallet::base58_to_wallet(&pkey_bs58) {
-                Ok(wallet) => {
-                    utils::wallet::save_wallet_to_db(&wallet, &db, &name).unwrap();
-                    println!("Wallet created and saved successfully!");
+        Commands::Label {
+            name,
+            description,
+            tags,
+            untags,
+            cluster,
+        } => {
+            // Stored as the moniker when the URL is a well-known one
+            let cluster = match cluster.as_deref() {
+                None => None,
+                Some("") => Some(None),
+                Some(cluster) => Some(Some(cluster.parse::<Cluster>()?.to_string())),
+            };
+            utils::wallet::update_wallet_meta(&db, &name, |meta| {
+                if let Some(description) = description {
+                    meta.description = Some(description).filter(|d| !d.is_empty());
+                }
+                if let Some(cluster) = cluster {
+                    meta.cluster = cluster;
                 }
-                Err(e) => {
-                    eprintln!("Error creating wallet: {}", e);
+                meta.tags.extend(tags);
+                for tag in &untags {
+                    meta.tags.remove(tag);
                 }
+            })?;
+            let meta = utils::wallet::read_wallet_meta(&db, &name)?;
+
+            Report::new(
+                format!("Wallet {} labeled", name),
+                json!({ "name": name, "meta": meta }),
+            )
+        }
+        Commands::Watch {
+            name,
+            pubkey,
+            description,
+        } => {
+            utils::address::add_watch_wallet(&db, &name, &pubkey, description, force)?;
+
+            Report::new(
+                format!("Watching {}: {}", name, pubkey),
+                json!({ "name": name, "pubkey": pubkey }),
+            )
+        }
+        Commands::Contact {
+            command:
+                ContactCommands::Add {
+                    name,
+                    pubkey,
+                    description,
+                },
+        } => {
+            utils::address::add_contact(&db, &name, &pubkey, description, force)?;
+
+            Report::new(
+                format!("Contact {} saved: {}", name, pubkey),
+                json!({ "name": name, "pubkey": pubkey }),
+            )
+        }
+        Commands::Contact {
+            command: ContactCommands::Remove { name },
+        } => {
+            utils::address::remove_contact(&db, &name)?;
+
+            Report::new(format!("Contact {} removed", name), json!({ "name": name }))
+        }
+        Commands::Contact {
+            command: ContactCommands::List,
+        } => {
+            let contacts = utils::address::list_contacts(&db)?;
+
+            let lines: Vec<String> = contacts
+                .iter()
+                .map(|(name, contact)| {
+                    format!(
+                        "{} {} {}",
+                        name,
+                        contact.pubkey,
+                        contact.description.as_deref().unwrap_or("")
+                    )
+                })
+                .collect();
+            let contacts: Vec<_> = contacts
+                .iter()
+                .map(|(name, contact)| {
+                    json!({
+                        "name": name,
+                        "pubkey": contact.pubkey,
+                        "description": contact.description,
+                    })
+                })
+                .collect();
+
+            Report::new(lines.join("\n"), json!({ "contacts": contacts }))
+        }
+        Commands::Base58ToWallet { pkey_bs58, name } => {
+            let wallet = utils::wallet::base58_to_wallet(&pkey_bs58)?;
+            let passphrase = passphrase.confirmed()?;
+            utils::wallet::save_wallet_to_db(
+                &wallet,
+                &db,
+                &name,
+                &passphrase,
+                force,
+                Origin::Base58Import,
+            )?;
+
+            Report::new(
+                "Wallet created and saved successfully!",
+                json!({ "name": name, "pubkey": wallet.pubkey }),
+            )
+        }
+        Commands::WalletToBase58 { name, reveal } => {
+            if !reveal {
+                return Err(ToolkitError::InvalidInput(format!(
+                    "This prints the secret key of {} in clear, pass --reveal to confirm",
+                    name
+                )));
             }
+            let wallet = utils::wallet::read_wallet(&db, &name, &passphrase)?;
+            let pubkey = wallet.pubkey.clone();
+            let base58 = utils::wallet::wallet_to_base58(wallet);
+
+            Report::new(
+                format!("Your private key in base58 is: {}", base58),
+                json!({ "name": name, "pubkey": pubkey, "secret_key": base58 }),
+            )
         }
-        Commands::WalletToBase58 { name } => {
-            let wallet = utils::wallet::read_wallet(&db, &name);
+        Commands::Balance { addresses, all } => {
+            // Resolve everything first so a typo fails before any request is sent
+            let targets: Vec<(String, Pubkey, Cluster)> = if all {
+                utils::wallet::list_wallet_entries(
+                    &db,
+                    &passphrase,
+                    &WalletFilter::default(),
+                    SortKey::Name,
+                )?
+                .into_iter()
+                // Seed wallets hold no funds themselves, their children are listed
+                .filter_map(|entry| Some((entry.pubkey?, entry.name)))
+                .map(|(pubkey, name)| {
+                    let pubkey = utils::address::parse_pubkey(&pubkey)?;
+                    let cluster = resolve_cluster(&cluster_url, &db, &name)?;
+                    Ok((name, pubkey, cluster))
+                })
+                .collect::<Result<_, ToolkitError>>()?
+            } else {
+                addresses
+                    .into_iter()
+                    .map(|address| {
+                        let pubkey = utils::address::resolve_address(&db, &address, &passphrase)?;
+                        let cluster = resolve_cluster(&cluster_url, &db, &address)?;
+                        Ok((address, pubkey, cluster))
+                    })
+                    .collect::<Result<_, ToolkitError>>()?
+            };
+
+            let mut lines = Vec::new();
+            let mut balances = Vec::new();
+            for (address, pubkey, cluster) in targets {
+                let client = utils::solana::rpc_client(&cluster, commitment);
+                let lamports = utils::solana::get_balance(&client, &pubkey)?;
+
+                lines.push(format!(
+                    "{} {} {} SOL ({} lamports) on {}",
+                    address,
+                    pubkey,
+                    format_sol(lamports),
+                    lamports,
+                    cluster
+                ));
+                balances.push(json!({
+                    "address": address,
+                    "pubkey": pubkey.to_string(),
+                    "cluster": cluster.to_string(),
+                    "lamports": lamports,
+                    "sol": format_sol(lamports),
+                }));
+            }
+
+            Report::new(lines.join("\n"), json!({ "balances": balances }))
+        }
+        Commands::Account { address, encoding } => {
+            let pubkey = utils::address::resolve_address(&db, &address, &passphrase)?;
+            let cluster = resolve_cluster(&cluster_url, &db, &address)?;
+            let client = utils::solana::rpc_client(&cluster, commitment);
+
+            let account = utils::solana::get_account(&client, &pubkey)?;
+
+            let mut lines = vec![
+                format!("Account {} on {}", pubkey, cluster),
+                format!("Owner: {}", account.owner),
+                format!(
+                    "Balance: {} SOL ({} lamports)",
+                    format_sol(account.lamports),
+                    account.lamports
+                ),
+                format!("Executable: {}", account.executable),
+                format!("Rent epoch: {}", account.rent_epoch),
+                format!("Data: {} bytes", account.data.len()),
+            ];
+            if !account.data.is_empty() {
+                lines.push(encoding.dump(&account.data));
+            }
 
-            utils::wallet::wallet_to_base58(wallet);
+            Report::new(
+                lines.join("\n"),
+                json!({
+                    "pubkey": pubkey.to_string(),
+                    "cluster": cluster.to_string(),
+                    "owner": account.owner.to_string(),
+                    "lamports": account.lamports,
+                    "sol": format_sol(account.lamports),
+                    "executable": account.executable,
+                    "rent_epoch": account.rent_epoch,
+                    "data_len": account.data.len(),
+                    "encoding": encoding,
+                    "data": encoding.encode(&account.data),
+                }),
+            )
         }
-        Commands::Airdrop { name } => {
-            let wallet = utils::wallet::read_wallet(&db, &name);
+        Commands::Airdrop {
+            name,
+            amount,
+            top_up,
+            retries,
+        } => {
+            if simulate {
+                return Err(ToolkitError::InvalidInput(
+                    "Airdrops are sent by the faucet, there is no transaction to simulate"
+                        .to_string(),
+                ));
+            }
+            let amount = amount.as_deref().map(faucet_lamports).transpose()?;
+            let top_up = top_up.as_deref().map(faucet_lamports).transpose()?;
+            let pubkey = utils::address::resolve_address(&db, &name, &passphrase)?;
+            let cluster = resolve_cluster(&cluster_url, &db, &name)?;
+            let client = utils::solana::rpc_client(&cluster, commitment);
+            if !cluster.has_faucet(|| Ok(client.get_genesis_hash()?))? {
+                let on = match &cluster {
+                    Cluster::Custom(url) => format!("mainnet-beta ({})", url),
+                    _ => cluster.to_string(),
+                };
+                return Err(ToolkitError::InvalidInput(format!(
+                    "Airdrops are not available on {}, only on devnet, testnet and localnet",
+                    on
+                )));
+            }
 
-            utils::solana::airdop(wallet, &cluster_url);
+            let lamports = match top_up {
+                Some(target) => {
+                    let balance = utils::solana::get_balance(&client, &pubkey)?;
+                    target.saturating_sub(balance)
+                }
+                None => amount.unwrap_or(utils::solana::AIRDROP_LAMPORTS),
+            };
+            if lamports == 0 {
+                return Ok(Report::new(
+                    format!("{} already holds enough SOL, nothing requested", pubkey),
+                    json!({
+                        "name": name,
+                        "pubkey": pubkey.to_string(),
+                        "cluster": cluster.to_string(),
+                        "lamports": 0,
+                        "signature": null,
+                    }),
+                ));
+            }
+
+            let signature = utils::solana::airdop(&pubkey, lamports, retries, &client)?;
+            let explorer = explorer.tx_url(&cluster, &signature);
+
+            Report::new(
+                format!(
+                    "Airdropped {} SOL ({} lamports) to {}\nSuccess! Check out your TX here:\n{}",
+                    format_sol(lamports),
+                    lamports,
+                    pubkey,
+                    explorer
+                ),
+                json!({
+                    "name": name,
+                    "pubkey": pubkey.to_string(),
+                    "cluster": cluster.to_string(),
+                    "lamports": lamports,
+                    "sol": format_sol(lamports),
+                    "signature": signature.to_string(),
+                    "explorer": explorer,
+                }),
+            )
         }
         Commands::Transfer { from, to, amount } => {
-            let wallet = utils::wallet::read_wallet(&db, &from);
-            // Convert the string amount to u64
-            let amount = amount
-                .parse::<u64>()
-                .expect("Failed to parse amount into u64");
+            let amount = amount.parse::<Amount>()?;
+            let to = utils::address::resolve_address(&db, &to, &passphrase)?;
+            let wallet = utils::wallet::read_wallet(&db, &from, &passphrase)?;
+            let cluster = resolve_cluster(&cluster_url, &db, &from)?;
+            let client = utils::solana::rpc_client(&cluster, commitment);
+            let fee_payer = read_fee_payer(&db, &fee_payer, &passphrase)?;
+
+            let (outcome, summary) = utils::solana::transfer_sol(
+                wallet,
+                &to,
+                amount,
+                fee_payer,
+                &client,
+                &send_options,
+                |summary| confirm(&describe_transfer(summary, &cluster), yes || simulate),
+            )?;
+            let (sent, paid) = match outcome {
+                Outcome::Sent(_) => ("Sent", "paid"),
+                Outcome::Simulated(_) => ("Would send", "paying"),
+            };
 
-            utils::solana::transfer_sol(wallet, &to, amount, &cluster_url);
+            Report::new(
+                format!(
+                    "{} {} SOL, {} {} lamports in fees\n{}",
+                    sent,
+                    format_sol(summary.amount),
+                    paid,
+                    summary.fee,
+                    outcome_text(&outcome, &cluster, explorer)
+                ),
+                with_outcome(
+                    json!({
+                        "from": summary.from.to_string(),
+                        "to": summary.to.to_string(),
+                        "cluster": cluster.to_string(),
+                        "lamports": summary.amount,
+                        "sol": format_sol(summary.amount),
+                        "fee": summary.fee,
+                        "fee_payer": summary.fee_payer.to_string(),
+                    }),
+                    &outcome,
+                    &cluster,
+                    explorer,
+                ),
+            )
         }
-        Commands::CleanWallet { from, to } => {
-            let wallet = utils::wallet::read_wallet(&db, &from);
+        Commands::CleanWallet {
+            from,
+            to,
+            move_tokens,
+        } => {
+            let to = utils::address::resolve_address(&db, &to, &passphrase)?;
+            let wallet = utils::wallet::read_wallet(&db, &from, &passphrase)?;
+            let pubkey = wallet.pubkey.clone();
+            let cluster = resolve_cluster(&cluster_url, &db, &from)?;
+            let client = utils::solana::rpc_client(&cluster, commitment);
+            let fee_payer = read_fee_payer(&db, &fee_payer, &passphrase)?;
+            let fee_payer_pubkey = fee_payer.as_ref().map(|wallet| wallet.pubkey.clone());
 
-            utils::solana::clean_wallet(wallet, &to, &cluster_url);
+            let sweep = utils::solana::clean_wallet(
+                wallet,
+                &to,
+                move_tokens,
+                fee_payer,
+                &client,
+                &send_options,
+                |plan| confirm(&describe_sweep(plan, &cluster), yes || simulate),
+            )?;
+            // A simulated sweep leaves the wallet as it was
+            let (swept, paid) = match sweep.outcome {
+                Outcome::Sent(_) => {
+                    utils::wallet::update_wallet_meta(&db, &from, |meta| {
+                        meta.swept_at = Some(utils::meta::now())
+                    })?;
+                    ("Swept", "paid")
+                }
+                Outcome::Simulated(_) => ("Would sweep", "paying"),
+            };
+
+            let mut lines = vec![format!(
+                "{} {} SOL ({} lamports), {} {} lamports in fees",
+                swept,
+                format_sol(sweep.amount),
+                sweep.amount,
+                paid,
+                sweep.fee
+            )];
+            lines.extend(
+                sweep
+                    .token_accounts
+                    .iter()
+                    .map(|token| format!("  closed: {} (mint {})", token.address, token.mint)),
+            );
+            lines.extend(
+                sweep
+                    .token_outcomes
+                    .iter()
+                    .map(|outcome| outcome_text(outcome, &cluster, explorer)),
+            );
+            lines.push(outcome_text(&sweep.outcome, &cluster, explorer));
+            let token_accounts: Vec<_> = sweep
+                .token_accounts
+                .iter()
+                .map(|token| {
+                    json!({
+                        "address": token.address.to_string(),
+                        "mint": token.mint.to_string(),
+                        "program": token.program.to_string(),
+                        "amount": token.amount,
+                        "lamports": token.lamports,
+                    })
+                })
+                .collect();
+
+            Report::new(
+                lines.join("\n"),
+                with_outcome(
+                    json!({
+                        "from": pubkey,
+                        "to": to.to_string(),
+                        "cluster": cluster.to_string(),
+                        "balance": sweep.balance,
+                        "fee": sweep.fee,
+                        "fee_payer": fee_payer_pubkey,
+                        "lamports": sweep.amount,
+                        "sol": format_sol(sweep.amount),
+                        "token_accounts": token_accounts,
+                        "token_transactions": sweep
+                            .token_outcomes
+                            .iter()
+                            .map(|outcome| with_outcome(json!({}), outcome, &cluster, explorer))
+                            .collect::<Vec<_>>(),
+                    }),
+                    &sweep.outcome,
+                    &cluster,
+                    explorer,
+                ),
+            )
         }
-    }
+        Commands::Prereq {
+            command: PrereqCommands::Status { wallet },
+        } => {
+            let signer = utils::address::resolve_address(&db, &wallet, &passphrase)?;
+            let cluster = resolve_cluster(&cluster_url, &db, &wallet)?;
+            let client = utils::solana::rpc_client(&cluster, commitment);
+            let (prereq, account) = utils::prereq::fetch_prereq(&client, &signer)?;
+
+            Report::new(
+                match &account {
+                    Some(account) => format!(
+                        "{} has submitted, prereq account {} holds {} bytes",
+                        signer,
+                        prereq,
+                        account.data.len()
+                    ),
+                    None => format!(
+                        "{} has not submitted, no prereq account at {}",
+                        signer, prereq
+                    ),
+                },
+                json!({
+                    "wallet": signer.to_string(),
+                    "prereq": prereq.to_string(),
+                    "cluster": cluster.to_string(),
+                    "submitted": account.is_some(),
+                    "lamports": account.as_ref().map(|account| account.lamports),
+                    "data_len": account.as_ref().map(|account| account.data.len()),
+                }),
+            )
+        }
+        Commands::Prereq {
+            command: PrereqCommands::Show { wallet },
+        } => {
+            let signer = utils::address::resolve_address(&db, &wallet, &passphrase)?;
+            let cluster = resolve_cluster(&cluster_url, &db, &wallet)?;
+            let client = utils::solana::rpc_client(&cluster, commitment);
+            let (prereq, bump) = utils::prereq::find_prereq_address(&signer);
+            let account = utils::prereq::read_prereq(&client, &signer)?;
+
+            let text = match &account {
+                Some(account) => [
+                    format!("GitHub: {}", account.github_handle()),
+                    format!("Key:    {}", account.key),
+                    format!("PDA:    {} (bump {})", prereq, bump),
+                ]
+                .join("\n"),
+                None => format!(
+                    "{} not completed, no prereq account at {} (bump {})",
+                    signer, prereq, bump
+                ),
+            };
+
+            Report::new(
+                text,
+                json!({
+                    "wallet": signer.to_string(),
+                    "cluster": cluster.to_string(),
+                    "completed": account.is_some(),
+                    "github": account.as_ref().map(|account| account.github_handle()),
+                    "key": account.as_ref().map(|account| account.key.to_string()),
+                    "prereq": prereq.to_string(),
+                    "bump": bump,
+                }),
+            )
+        }
+        Commands::Prereq {
+            command: PrereqCommands::Audit { roster },
+        } => {
+            let entries = utils::prereq::read_roster(&roster)?;
+            let cluster = cluster_url.clone().unwrap_or_default();
+            let client = utils::solana::rpc_client(&cluster, commitment);
+            let rows = utils::prereq::audit_roster(&client, entries)?;
+
+            let count =
+                |status: AuditStatus| rows.iter().filter(|row| row.status == status).count();
+            let (completed, missing, mismatched, invalid) = (
+                count(AuditStatus::Completed),
+                count(AuditStatus::Missing),
+                count(AuditStatus::GithubMismatch),
+                count(AuditStatus::Invalid),
+            );
+
+            let mut lines: Vec<String> = rows
+                .iter()
+                .map(|row| {
+                    let detail = match (&row.stored_github, &row.reason) {
+                        (_, Some(reason)) => format!(" ({})", reason),
+                        (Some(stored), None) if row.status == AuditStatus::GithubMismatch => {
+                            format!(" (on chain: {})", stored)
+                        }
+                        _ => String::new(),
+                    };
+                    format!(
+                        "{:<15} {} {}{}",
+                        row.status, row.entry.github, row.entry.wallet, detail
+                    )
+                })
+                .collect();
+            lines.push(format!(
+                "{} rows: {} completed, {} missing, {} mismatched, {} invalid",
+                rows.len(),
+                completed,
+                missing,
+                mismatched,
+                invalid
+            ));
+
+            Report::new(
+                lines.join("\n"),
+                json!({
+                    "roster": roster.display().to_string(),
+                    "cluster": cluster.to_string(),
+                    "completed": completed,
+                    "missing": missing,
+                    "mismatched": mismatched,
+                    "invalid": invalid,
+                    "rows": rows
+                        .iter()
+                        .map(|row| json!({
+                            "line": row.entry.line,
+                            "github": row.entry.github,
+                            "wallet": row.entry.wallet.to_string(),
+                            "prereq": row.prereq.to_string(),
+                            "status": row.status.to_string(),
+                            "stored_github": row.stored_github,
+                            "reason": row.reason,
+                        }))
+                        .collect::<Vec<_>>(),
+                }),
+            )
+        }
+        Commands::Prereq {
+            command: PrereqCommands::ListAll { github, duplicates },
+        } => {
+            let cluster = cluster_url.clone().unwrap_or_default();
+            let client = utils::solana::rpc_client(&cluster, commitment);
+            let entries = utils::prereq::list_prereqs(&client)?;
+            // Duplicates are counted cohort-wide, before filtering
+            let duplicate_handles = utils::prereq::duplicate_handles(&entries);
+            let total = entries.len();
+
+            let filter = github.map(|github| github.to_lowercase());
+            let entries: Vec<_> = entries
+                .into_iter()
+                .map(|entry| {
+                    let handle = entry.account.github_handle();
+                    let duplicate = duplicate_handles.contains(&handle.to_lowercase());
+                    (entry, handle, duplicate)
+                })
+                .filter(|(_, handle, duplicate)| {
+                    filter
+                        .as_ref()
+                        .is_none_or(|filter| handle.to_lowercase().contains(filter))
+                        && (!duplicates || *duplicate)
+                })
+                .collect();
+
+            let mut lines: Vec<String> = entries
+                .iter()
+                .map(|(entry, handle, duplicate)| {
+                    format!(
+                        "{} {} {}{}",
+                        entry.prereq,
+                        entry.account.key,
+                        handle,
+                        if *duplicate { " (duplicate)" } else { "" }
+                    )
+                })
+                .collect();
+            lines.push(format!(
+                "{} of {} prereq accounts, {} duplicate handles",
+                entries.len(),
+                total,
+                duplicate_handles.len()
+            ));
+
+            Report::new(
+                lines.join("\n"),
+                json!({
+                    "cluster": cluster.to_string(),
+                    "total": total,
+                    "duplicates": duplicate_handles,
+                    "accounts": entries
+                        .iter()
+                        .map(|(entry, handle, duplicate)| json!({
+                            "prereq": entry.prereq.to_string(),
+                            "key": entry.account.key.to_string(),
+                            "github": handle,
+                            "duplicate": duplicate,
+                        }))
+                        .collect::<Vec<_>>(),
+                }),
+            )
+        }
+        Commands::Prereq { command } => {
+            let (wallet, github, action) = match command {
+                PrereqCommands::Submit { wallet, github } => (wallet, github, PrereqAction::Submit),
+                PrereqCommands::Update { wallet, github } => (wallet, github, PrereqAction::Update),
+                PrereqCommands::Status { .. }
+                | PrereqCommands::Show { .. }
+                | PrereqCommands::Audit { .. }
+                | PrereqCommands::ListAll { .. } => {
+                    unreachable!("handled above")
+                }
+            };
+            // The generated client signs whole transactions, ComputeBudget instructions
+            // cannot be added to them
+            if send_options.compute_budget != ComputeBudget::default() {
+                return Err(ToolkitError::InvalidInput(
+                    "Prereq transactions do not take --priority-fee, --auto-fee or --compute-unit-limit"
+                        .to_string(),
+                ));
+            }
+            let signer = utils::wallet::read_wallet(&db, &wallet, &passphrase)?;
+            let cluster = resolve_cluster(&cluster_url, &db, &wallet)?;
+            let client = utils::solana::rpc_client(&cluster, commitment);
+            let fee_payer = read_fee_payer(&db, &fee_payer, &passphrase)?;
+
+            let (outcome, summary) = utils::prereq::send_prereq(
+                signer,
+                action,
+                &github,
+                fee_payer,
+                &client,
+                simulate,
+                |summary| confirm(&describe_prereq(summary, &cluster), yes || simulate),
+            )?;
+            let done = match (&outcome, action) {
+                (Outcome::Simulated(_), PrereqAction::Submit) => "Would submit",
+                (Outcome::Simulated(_), PrereqAction::Update) => "Would update",
+                (Outcome::Sent(_), PrereqAction::Submit) => "Submitted",
+                (Outcome::Sent(_), PrereqAction::Update) => "Updated",
+            };
+
+            Report::new(
+                format!(
+                    "{} prereq of {} with GitHub handle {}\n{}",
+                    done,
+                    summary.signer,
+                    summary.github,
+                    outcome_text(&outcome, &cluster, explorer)
+                ),
+                with_outcome(
+                    json!({
+                        "wallet": summary.signer.to_string(),
+                        "prereq": summary.prereq.to_string(),
+                        "github": summary.github,
+                        "cluster": cluster.to_string(),
+                        "fee": summary.fee,
+                        "fee_payer": summary.fee_payer.to_string(),
+                    }),
+                    &outcome,
+                    &cluster,
+                    explorer,
+                ),
+            )
+        }
+        Commands::Db {
+            command: DbCommands::Migrate { dry_run },
+        } => {
+            // Encrypting plaintext secrets is for good, a typed passphrase is confirmed first
+            let passphrase = match utils::db::has_plaintext_secrets(&db)? {
+                false => None,
+                true if dry_run => Some(passphrase.passphrase()?),
+                true => Some(passphrase.confirmed()?),
+            };
+            let report = utils::db::migrate(&db, passphrase.as_deref(), dry_run)?;
+
+            for (key, e) in &report.failed {
+                eprintln!("  failed: {}: {}", key, e);
+            }
+            if !report.failed.is_empty() {
+                return Err(ToolkitError::Decode(
+                    "Nothing was written, fix or remove the failed records first".to_string(),
+                ));
+            }
+
+            let mut lines = vec![format!("Schema version {} -> {}", report.from, report.to)];
+            lines.extend(
+                report
+                    .applied
+                    .iter()
+                    .map(|description| format!("  migration: {}", description)),
+            );
+            lines.extend(
+                report
+                    .upgraded
+                    .iter()
+                    .map(|key| format!("  upgraded: {}", key)),
+            );
+            if dry_run {
+                lines.push("Dry run, nothing was written".to_string());
+            }
+
+            Report::new(
+                lines.join("\n"),
+                json!({
+                    "from": report.from,
+                    "to": report.to,
+                    "applied": report.applied,
+                    "upgraded": report.upgraded,
+                    "dry_run": dry_run,
+                }),
+            )
+        }
+        Commands::Db {
+            command: DbCommands::Check { decrypt },
+        } => {
+            let passphrase = if decrypt {
+                Some(passphrase.passphrase()?)
+            } else {
+                None
+            };
+
+            let issues = utils::db::check_db(&db, passphrase.as_deref())?;
+            if !issues.is_empty() {
+                for issue in &issues {
+                    eprintln!("{}: {}", issue.key, issue.problem);
+                }
+                return Err(ToolkitError::Decode(format!(
+                    "{} problems found",
+                    issues.len()
+                )));
+            }
+
+            Report::new("Database is healthy", json!({ "healthy": true }))
+        }
+        Commands::Config { .. } | Commands::Profile { .. } => {
+            unreachable!("config commands are handled before the wallet store is opened")
+        }
+    };
+
+    Ok(report)
+}
+
+/// The config file and its contents, empty when it does not exist yet.
+fn config_file(config: Option<PathBuf>) -> Result<(PathBuf, Config), ToolkitError> {
+    let path = config.or_else(Config::default_path).ok_or_else(|| {
+        ToolkitError::InvalidInput("Cannot locate the config file, pass --config".to_string())
+    })?;
+    let config = Config::load(&path)?;
+
+    Ok((path, config))
+}
+
+fn run_config(
+    config: Option<PathBuf>,
+    profile: Option<String>,
+    command: ConfigCommands,
+) -> Result<Report, ToolkitError> {
+    let (path, mut config) = config_file(config)?;
+    let profile = profile.as_deref();
+    let profile_name = config.profile_name(profile).to_string();
+
+    let report = match command {
+        ConfigCommands::Get { key } => {
+            let value = config.profile(profile)?.get(key).ok_or_else(|| {
+                ToolkitError::NotFound(format!("{} is not set in profile {}", key, profile_name))
+            })?;
+
+            Report::new(
+                value.clone(),
+                json!({ "profile": profile_name, "key": key.to_string(), "value": value }),
+            )
+        }
+        ConfigCommands::Set { key, value } => {
+            config.profile_mut(profile).set(key, &value)?;
+            config.save(&path)?;
+            let value = config.profile(profile)?.get(key);
+
+            Report::new(
+                match &value {
+                    Some(value) => format!("Set {} to {} in profile {}", key, value, profile_name),
+                    None => format!("Cleared {} in profile {}", key, profile_name),
+                },
+                json!({ "profile": profile_name, "key": key.to_string(), "value": value }),
+            )
+        }
+        ConfigCommands::Show => Report::new(
+            format!("# {}\n{}", path.display(), config.to_toml()?),
+            json!({
+                "path": path.display().to_string(),
+                "active_profile": config.active_profile,
+                "profiles": config.profiles,
+            }),
+        ),
+    };
+
+    Ok(report)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use assert_cmd::Command;
-    use predicates::str::contains;
-    use solana_sdk::{signature::Keypair, signer::Signer};
+    use predicates::{prelude::PredicateBooleanExt, str::contains};
+    use solana_sdk::signature::Keypair;
+    use std::fs;
     use tempdir::TempDir;
     use utils::wallet::{get_wallet_key, read_wallet};
 
+    const PASSPHRASE: &str = "test passphrase";
+
+    /// The CLI binary, kept away from the config file of whoever runs the tests.
+    fn toolkit() -> Command {
+        let mut command = Command::cargo_bin("turbin3_pre_req").unwrap();
+        command
+            .env("WBA_TOOLKIT_CONFIG", "/nonexistent/wba_toolkit/config.toml")
+            .env_remove("WBA_TOOLKIT_PROFILE");
+
+        command
+    }
+
     #[test]
     fn test_keygen_command() {
         let tmp_dir = TempDir::new("wallet_db").unwrap();
         let db_path = tmp_dir.path().to_str().unwrap();
 
-        Command::cargo_bin("turbin3_pre_req")
-            .unwrap()
+        toolkit()
+            .env("WBA_TOOLKIT_PASSPHRASE", PASSPHRASE)
             .args(&["keygen", "test_wallet", "--db-path", db_path])
             .assert()
             .success()
-            .stdout(contains("saved to RocksDB"));
+            .stdout(contains("saved to the wallet store"));
 
         // Verify the wallet was stored in RocksDB
-        let db = DB::open_default(db_path).unwrap();
-        let wallet = read_wallet(&db, "test_wallet");
+        let db = utils::store::open_store(db_path, None).unwrap();
+        let wallet = read_wallet(&db, "test_wallet", PASSPHRASE).unwrap();
         assert_eq!(wallet.pubkey.len(), 44); // Check that the pubkey is of correct length
         assert_eq!(wallet.secret_key.len(), 64); // Check that the secret_key is of correct length
+
+        // Other backends are not reported as RocksDB
+        toolkit()
+            .env("WBA_TOOLKIT_PASSPHRASE", PASSPHRASE)
+            .args(&["keygen", "test_wallet", "--store", "memory"])
+            .assert()
+            .success()
+            .stdout(contains("saved to the wallet store").and(contains("RocksDB").not()));
     }
 
     #[test]
@@ -146,21 +2035,21 @@ mod tests {
         let db_path = tmp_dir.path().to_str().unwrap();
 
         // Create multiple wallets
-        Command::cargo_bin("turbin3_pre_req")
-            .unwrap()
+        toolkit()
+            .env("WBA_TOOLKIT_PASSPHRASE", PASSPHRASE)
             .args(&["keygen", "test_wallet_1", "--db-path", db_path])
             .assert()
             .success();
 
-        Command::cargo_bin("turbin3_pre_req")
-            .unwrap()
+        toolkit()
+            .env("WBA_TOOLKIT_PASSPHRASE", PASSPHRASE)
             .args(&["keygen", "test_wallet_2", "--db-path", db_path])
             .assert()
             .success();
 
         // List wallets
-        Command::cargo_bin("turbin3_pre_req")
-            .unwrap()
+        toolkit()
+            .env("WBA_TOOLKIT_PASSPHRASE", PASSPHRASE)
             .args(&["list-wallets", "--db-path", db_path])
             .assert()
             .success()
@@ -168,6 +2057,52 @@ mod tests {
             .stdout(predicates::str::contains(get_wallet_key("test_wallet_2")));
     }
 
+    #[test]
+    fn test_label_and_filter_wallets() {
+        let tmp_dir = TempDir::new("wallet_db").unwrap();
+        let db_path = tmp_dir.path().to_str().unwrap();
+
+        let run = |args: &[&str]| {
+            toolkit()
+                .env("WBA_TOOLKIT_PASSPHRASE", PASSPHRASE)
+                .args(args)
+                .args(&["--db-path", db_path])
+                .assert()
+                .success()
+        };
+
+        run(&["keygen", "authority"]);
+        run(&["keygen", "payer"]);
+        run(&[
+            "label",
+            "authority",
+            "--description",
+            "Program upgrade authority",
+            "--tag",
+            "mainnet",
+        ]);
+
+        run(&["list-wallets", "--tag", "mainnet"])
+            .stdout(contains(get_wallet_key("authority")))
+            .stdout(contains("Program upgrade authority"))
+            .stdout(contains(get_wallet_key("payer")).not());
+        run(&["list-wallets", "--origin", "generated", "--sort", "created"])
+            .stdout(contains(get_wallet_key("authority")))
+            .stdout(contains(get_wallet_key("payer")));
+
+        // Well-known URLs are stored as their moniker and match either form
+        run(&[
+            "label",
+            "payer",
+            "--cluster",
+            "https://api.devnet.solana.com",
+        ])
+        .stdout(contains("devnet"));
+        run(&["list-wallets", "--cluster", "devnet"])
+            .stdout(contains(get_wallet_key("payer")))
+            .stdout(contains(get_wallet_key("authority")).not());
+    }
+
     #[test]
     fn test_base58_to_wallet_command() {
         let tmp_dir = TempDir::new("wallet_db").unwrap();
@@ -179,8 +2114,8 @@ mod tests {
         let wallet_name = "test_wallet";
 
         // Run the command
-        Command::cargo_bin("turbin3_pre_req")
-            .unwrap()
+        toolkit()
+            .env("WBA_TOOLKIT_PASSPHRASE", PASSPHRASE)
             .args(&[
                 "base58-to-wallet",
                 &base58_secret_key,
@@ -195,10 +2130,122 @@ mod tests {
             ));
 
         // Verify the wallet was stored in RocksDB
-        let db = DB::open_default(db_path).unwrap();
-        let wallet = read_wallet(&db, wallet_name);
+        let db = utils::store::open_store(db_path, None).unwrap();
+        let wallet = read_wallet(&db, wallet_name, PASSPHRASE).unwrap();
         assert_eq!(wallet.pubkey, keypair.pubkey().to_string());
-        assert_eq!(wallet.secret_key, keypair.to_bytes().to_vec());
+        assert_eq!(wallet.secret_key[..], keypair.to_bytes()[..]);
+    }
+
+    #[test]
+    fn test_keygen_mnemonic_and_recover_commands() {
+        let tmp_dir = TempDir::new("wallet_db").unwrap();
+        let db_path = tmp_dir.path().to_str().unwrap();
+
+        let output = toolkit()
+            .env("WBA_TOOLKIT_PASSPHRASE", PASSPHRASE)
+            .args(&["keygen", "seeded", "--mnemonic", "12", "--db-path", db_path])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+
+        // The seed phrase is the line printed after the backup notice
+        let stdout = String::from_utf8(output).unwrap();
+        let phrase = stdout
+            .lines()
+            .skip_while(|line| !line.starts_with("Write down"))
+            .nth(1)
+            .unwrap()
+            .to_string();
+
+        toolkit()
+            .env("WBA_TOOLKIT_PASSPHRASE", PASSPHRASE)
+            .args(&["recover", &phrase, "recovered", "--db-path", db_path])
+            .assert()
+            .success();
+
+        let db = utils::store::open_store(db_path, None).unwrap();
+        let seeded = read_wallet(&db, "seeded", PASSPHRASE).unwrap();
+        let recovered = read_wallet(&db, "recovered", PASSPHRASE).unwrap();
+        assert_eq!(seeded, recovered);
+    }
+
+    #[test]
+    fn test_grind_command() {
+        let tmp_dir = TempDir::new("wallet_db").unwrap();
+        let db_path = tmp_dir.path().to_str().unwrap();
+
+        toolkit()
+            .env("WBA_TOOLKIT_PASSPHRASE", PASSPHRASE)
+            .args(&[
+                "grind",
+                "vanity",
+                "--prefix",
+                "a",
+                "--ignore-case",
+                "--db-path",
+                db_path,
+            ])
+            .assert()
+            .success()
+            .stdout(contains("saved to the wallet store"));
+
+        let db = utils::store::open_store(db_path, None).unwrap();
+        let wallet = read_wallet(&db, "vanity", PASSPHRASE).unwrap();
+        assert!(wallet.pubkey.to_lowercase().starts_with('a'));
+    }
+
+    #[test]
+    fn test_export_and_import_file_commands() {
+        let tmp_dir = TempDir::new("wallet_db").unwrap();
+        let db_path = tmp_dir.path().join("db");
+        let db_path = db_path.to_str().unwrap();
+        let key_path = tmp_dir.path().join("id.json");
+        let key_path = key_path.to_str().unwrap();
+
+        toolkit()
+            .env("WBA_TOOLKIT_PASSPHRASE", PASSPHRASE)
+            .args(&["keygen", "original", "--db-path", db_path])
+            .assert()
+            .success();
+
+        toolkit()
+            .env("WBA_TOOLKIT_PASSPHRASE", PASSPHRASE)
+            .args(&["export-file", "original", key_path, "--db-path", db_path])
+            .assert()
+            .success()
+            .stdout(contains("exported"));
+
+        toolkit()
+            .env("WBA_TOOLKIT_PASSPHRASE", PASSPHRASE)
+            .args(&["import-file", key_path, "copy", "--db-path", db_path])
+            .assert()
+            .success()
+            .stdout(contains("imported"));
+
+        let db = utils::store::open_store(db_path, None).unwrap();
+        let original = read_wallet(&db, "original", PASSPHRASE).unwrap();
+        let copy = read_wallet(&db, "copy", PASSPHRASE).unwrap();
+        assert_eq!(original, copy);
+    }
+
+    #[test]
+    fn test_keygen_refuses_to_overwrite() {
+        let tmp_dir = TempDir::new("wallet_db").unwrap();
+        let db_path = tmp_dir.path().to_str().unwrap();
+
+        let keygen = |extra: &[&str]| {
+            toolkit()
+                .env("WBA_TOOLKIT_PASSPHRASE", PASSPHRASE)
+                .args(&["keygen", "funded", "--db-path", db_path])
+                .args(extra)
+                .assert()
+        };
+
+        keygen(&[]).success();
+        keygen(&[]).failure();
+        keygen(&["--force"]).success();
     }
 
     #[test]
@@ -206,28 +2253,310 @@ mod tests {
         let tmp_dir = TempDir::new("wallet_to_base58").expect("Failed to create a new folder");
         let db_path = tmp_dir.path().to_str().unwrap();
 
-        Command::cargo_bin("turbin3_pre_req")
-            .unwrap()
-            .args(&["keygen", "test_wallet", "--db-path", db_path])
+        toolkit()
+            .env("WBA_TOOLKIT_PASSPHRASE", PASSPHRASE)
+            .args(&["keygen", "test_wallet", "--db-path", db_path, "--verbose"])
             .assert()
             .success()
-            .stdout(contains("saved to RocksDB"));
+            .stdout(contains("saved to the wallet store"))
+            .stderr(contains("<redacted 64 bytes>"));
 
         // Verify the wallet was stored in RocksDB
         let base58_secret_key: String;
         {
-            let db = DB::open_default(&db_path).expect("Could not open RocksDB");
-            let wallet = read_wallet(&db, "test_wallet");
+            let db = utils::store::open_store(db_path, None).expect("Could not open wallet store");
+            let wallet = read_wallet(&db, "test_wallet", PASSPHRASE).unwrap();
             base58_secret_key = bs58::encode(&wallet.secret_key).into_string();
             drop(db); // Ensure the database is closed before running the next command
         }
 
-        // Run the command
-        Command::cargo_bin("turbin3_pre_req")
-            .unwrap()
+        // The secret is only printed once confirmed
+        toolkit()
+            .env("WBA_TOOLKIT_PASSPHRASE", PASSPHRASE)
             .args(&["wallet-to-base58", "test_wallet", "--db-path", db_path])
             .assert()
+            .code(12)
+            .stdout(contains(base58_secret_key.as_str()).not());
+
+        // Run the command
+        toolkit()
+            .env("WBA_TOOLKIT_PASSPHRASE", PASSPHRASE)
+            .args(&[
+                "wallet-to-base58",
+                "test_wallet",
+                "--reveal",
+                "--db-path",
+                db_path,
+            ])
+            .assert()
             .success()
             .stdout(predicates::str::contains(base58_secret_key));
     }
+
+    #[test]
+    fn test_db_migrate_and_check_commands() {
+        let tmp_dir = TempDir::new("wallet_db").unwrap();
+        let db_path = tmp_dir.path().to_str().unwrap();
+
+        // A plaintext record as written before secrets were encrypted
+        let keypair = Keypair::new();
+        let legacy = utils::wallet::Wallet {
+            pubkey: keypair.pubkey().to_string(),
+            secret_key: keypair.to_bytes().to_vec().into(),
+        };
+        {
+            let db = utils::store::open_store(db_path, None).unwrap();
+            db.put(
+                &get_wallet_key("legacy"),
+                &serde_json::to_vec(&legacy).unwrap(),
+            )
+            .unwrap();
+        }
+
+        let db = |args: &[&str]| {
+            toolkit()
+                .env("WBA_TOOLKIT_PASSPHRASE", PASSPHRASE)
+                .arg("db")
+                .args(args)
+                .args(["--db-path", db_path])
+                .assert()
+        };
+
+        db(&["check"]).failure().stderr(contains("wallet:legacy"));
+        // Other commands leave plaintext secrets alone
+        toolkit()
+            .env("WBA_TOOLKIT_PASSPHRASE", PASSPHRASE)
+            .args(["list-wallets", "--db-path", db_path])
+            .assert()
+            .code(14)
+            .stderr(contains("run `db migrate`"));
+        db(&["migrate", "--dry-run"])
+            .success()
+            .stdout(contains("upgraded: wallet:legacy").and(contains("Dry run")));
+        db(&["check"]).failure();
+        db(&["migrate"]).success();
+        db(&["check", "--decrypt"])
+            .success()
+            .stdout(contains("Database is healthy"));
+
+        let db = utils::store::open_store(db_path, None).unwrap();
+        assert_eq!(read_wallet(&db, "legacy", PASSPHRASE).unwrap(), legacy);
+    }
+
+    #[test]
+    fn test_watch_and_contact_commands() {
+        let tmp_dir = TempDir::new("wallet_db").unwrap();
+        let db_path = tmp_dir.path().to_str().unwrap();
+        let wba_pubkey = fs::read_to_string("wba-public-key").unwrap();
+        let wba_pubkey = wba_pubkey.trim();
+
+        let run = |args: &[&str]| {
+            toolkit()
+                .env("WBA_TOOLKIT_PASSPHRASE", PASSPHRASE)
+                .args(args)
+                .args(["--db-path", db_path])
+                .assert()
+        };
+
+        run(&["watch", "wba", wba_pubkey]).success();
+        run(&["contact", "add", "teammate", wba_pubkey]).success();
+        run(&["contact", "add", "teammate", wba_pubkey])
+            .code(11)
+            .stderr(contains("already exists"));
+        run(&["list-wallets", "--origin", "watch-only"])
+            .success()
+            .stdout(contains("watch:wba").and(contains(wba_pubkey)));
+        run(&["watch", "watch:wba", wba_pubkey]).code(12);
+        run(&["contact", "add", "contact:bob", wba_pubkey]).code(12);
+        run(&["contact", "list"])
+            .success()
+            .stdout(contains("teammate"));
+
+        // Unknown recipients fail before anything is sent
+        run(&["keygen", "payer"]).success();
+        run(&["transfer", "payer", "nobody", "1SOL"])
+            .code(10)
+            .stderr(contains("Unknown address nobody"));
+    }
+
+    #[test]
+    fn test_json_output() {
+        let tmp_dir = TempDir::new("wallet_db").unwrap();
+        let db_path = tmp_dir.path().to_str().unwrap();
+
+        let run = |args: &[&str]| {
+            toolkit()
+                .env("WBA_TOOLKIT_PASSPHRASE", PASSPHRASE)
+                .args(args)
+                .args(["--db-path", db_path, "--output", "json"])
+                .assert()
+        };
+        let document = |output: &std::process::Output| {
+            serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+        };
+
+        let keygen = document(run(&["keygen", "payer"]).success().get_output());
+        assert_eq!(keygen["name"], "payer");
+        let pubkey = keygen["pubkey"].as_str().unwrap().to_string();
+
+        let listed = document(run(&["list-wallets"]).success().get_output());
+        assert_eq!(listed["wallets"][0]["pubkey"], pubkey.as_str());
+        assert_eq!(listed["wallets"][0]["origin"], "generated");
+
+        let failed = document(run(&["read-keygen", "missing"]).code(10).get_output());
+        assert_eq!(failed["error"]["kind"], "not_found");
+        assert_eq!(failed["error"]["code"], 10);
+
+        // Debug chatter only shows up with --verbose, and never on stdout
+        run(&["read-keygen", "payer"])
+            .success()
+            .stderr(contains("Reading wallet").not());
+        run(&["read-keygen", "payer", "--verbose"])
+            .success()
+            .stderr(contains("Reading wallet"))
+            .stdout(contains("Reading wallet").not());
+    }
+
+    #[test]
+    fn test_config_profiles() {
+        let tmp_dir = TempDir::new("config").unwrap();
+        let config_path = tmp_dir.path().join("config.toml");
+        let db_path = tmp_dir.path().join("devnet_db");
+        let db_path = db_path.to_str().unwrap();
+
+        let run = |args: &[&str]| {
+            toolkit()
+                .env("WBA_TOOLKIT_PASSPHRASE", PASSPHRASE)
+                .env("WBA_TOOLKIT_CONFIG", &config_path)
+                .args(args)
+                .assert()
+        };
+
+        run(&["config", "set", "db-path", db_path, "--profile", "devnet"])
+            .success()
+            .stdout(contains("Set db-path"));
+        run(&["config", "set", "output", "json", "--profile", "devnet"]).success();
+        run(&["config", "set", "output", "yaml", "--profile", "devnet"]).code(12);
+        run(&["profile", "use", "localnet"]).code(10);
+        run(&["profile", "use", "devnet"]).success();
+
+        // The active profile supplies --db-path and --output
+        let keygen: serde_json::Value =
+            serde_json::from_slice(&run(&["keygen", "payer"]).success().get_output().stdout)
+                .unwrap();
+        assert_eq!(keygen["name"], "payer");
+        let db = utils::store::open_store(db_path, None).unwrap();
+        assert!(read_wallet(&db, "payer", PASSPHRASE).is_ok());
+        drop(db);
+
+        // Flags still win over the profile
+        run(&["read-keygen", "payer", "--output", "text"])
+            .success()
+            .stdout(contains("Wallet payer:"));
+        run(&["read-keygen", "payer", "--profile", "mainnet"]).code(10);
+
+        run(&["config", "get", "cluster"]).code(10);
+        run(&["config", "set", "cluster", "https://api.devnet.solana.com"]).success();
+        run(&["config", "get", "cluster"])
+            .success()
+            .stdout(contains("devnet"));
+        run(&["config", "show"])
+            .success()
+            .stdout(contains("[profiles.devnet]"))
+            .stdout(contains("active-profile = \"devnet\""));
+    }
+
+    #[test]
+    fn test_errors_exit_with_their_code() {
+        let tmp_dir = TempDir::new("wallet_db").unwrap();
+        let db_path = tmp_dir.path().to_str().unwrap();
+
+        let run = |args: &[&str]| {
+            toolkit()
+                .env("WBA_TOOLKIT_PASSPHRASE", PASSPHRASE)
+                .args(args)
+                .args(["--db-path", db_path])
+                .assert()
+        };
+
+        run(&["read-keygen", "missing"])
+            .code(10)
+            .stderr(contains("Error: Wallet missing not found"));
+        run(&["keygen", "bad", "--mnemonic", "15"]).code(12);
+        run(&["watch", "bad", "not a pubkey"]).code(13);
+        run(&["balance"]).code(2);
+        run(&["balance", "missing"]).code(10);
+        run(&["account", "missing"]).code(10);
+        run(&[
+            "airdrop",
+            "11111111111111111111111111111111",
+            "--amount",
+            "ALL",
+        ])
+        .code(12);
+        run(&[
+            "airdrop",
+            "11111111111111111111111111111111",
+            "--cluster-url",
+            "mainnet-beta",
+        ])
+        .code(12)
+        .stderr(contains("not available on mainnet-beta"));
+        run(&["airdrop", "11111111111111111111111111111111", "--simulate"])
+            .code(12)
+            .stderr(contains("no transaction to simulate"));
+        run(&[
+            "transfer",
+            "a",
+            "b",
+            "1",
+            "--priority-fee",
+            "10",
+            "--auto-fee",
+        ])
+        .code(2);
+        run(&["transfer", "a", "b", "1", "--auto-fee=101"]).code(2);
+        run(&["prereq", "submit", "missing"]).code(2);
+        run(&["prereq", "audit", "--roster", "missing.csv"])
+            .code(17)
+            .stderr(contains("Cannot read roster"));
+        run(&["transfer", "missing", "missing", "1.0000000001"])
+            .code(12)
+            .stderr(contains("at most 9 decimals"));
+        run(&["transfer", "missing", "missing", "100000", "--yes"])
+            .code(12)
+            .stderr(contains("needs a unit"));
+        run(&["base58-to-wallet", "0OIl", "bad"]).code(14);
+
+        run(&["keygen", "taken"]).success();
+        run(&["keygen", "taken"]).code(11);
+        run(&["prereq", "submit", "taken", "--github", "bad--handle"])
+            .code(12)
+            .stderr(contains("Invalid GitHub handle"));
+        run(&[
+            "prereq",
+            "update",
+            "taken",
+            "--github",
+            "kox",
+            "--priority-fee",
+            "5",
+        ])
+        .code(12)
+        .stderr(contains("do not take --priority-fee"));
+        run(&["label", "taken", "--cluster", "moonnet"]).code(12);
+
+        let wrong_passphrase = toolkit()
+            .env("WBA_TOOLKIT_PASSPHRASE", "wrong passphrase")
+            .args(["read-keygen", "taken", "--db-path", db_path])
+            .assert();
+        wrong_passphrase.code(15);
+        // A new wallet is never sealed under another passphrase than the others
+        toolkit()
+            .env("WBA_TOOLKIT_PASSPHRASE", "wrong passphrase")
+            .args(["keygen", "typo", "--db-path", db_path])
+            .assert()
+            .code(15)
+            .stderr(contains("does not open the wallets already in the store"));
+    }
 }
@@ -1 +1,3 @@
-pub mod wba_prereq; 
\ No newline at end of file
+// Generated from the IDL, the client has more than the CLI uses
+#[allow(dead_code)]
+pub mod wba_prereq;
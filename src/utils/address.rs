@@ -0,0 +1,373 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use super::crypto::PassphraseSource;
+use super::error::ToolkitError;
+use super::meta::{Origin, WalletMeta};
+use super::seed::{parse_child_name, read_child_wallet};
+use super::store::{StoreBatch, WalletStore};
+use super::wallet::{
+    check_name, ensure_name_available, read_wallet_pubkey, replace_wallet_record,
+};
+
+/// A watch-only wallet: an address we keep an eye on but hold no secret for.
+/// It shares the wallet namespace, so it can be labeled, listed and renamed like any wallet.
+#[derive(Serialize, Deserialize, Debug)]
+struct StoredWatch {
+    pubkey: String,
+    #[serde(default)]
+    meta: WalletMeta,
+}
+
+/// Someone else's address, kept under a name so it never has to be pasted again.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Contact {
+    pub pubkey: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+pub fn get_watch_key(name: &str) -> String {
+    format!("watch:{}", name)
+}
+
+pub fn get_contact_key(name: &str) -> String {
+    format!("contact:{}", name)
+}
+
+pub fn parse_pubkey(pubkey: &str) -> Result<Pubkey, ToolkitError> {
+    Pubkey::from_str(pubkey)
+        .map_err(|e| ToolkitError::InvalidPubkey(format!("Invalid pubkey {}: {}", pubkey, e)))
+}
+
+fn read_stored_watch(
+    db: &dyn WalletStore,
+    name: &str,
+) -> Result<Option<StoredWatch>, ToolkitError> {
+    match db.get(&get_watch_key(name))? {
+        Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+        None => Ok(None),
+    }
+}
+
+pub fn add_watch_wallet(
+    db: &dyn WalletStore,
+    name: &str,
+    pubkey: &str,
+    description: Option<String>,
+    force: bool,
+) -> Result<(), ToolkitError> {
+    let pubkey = parse_pubkey(pubkey)?;
+    ensure_name_available(db, name, force)?;
+
+    let stored = StoredWatch {
+        pubkey: pubkey.to_string(),
+        meta: WalletMeta {
+            description,
+            ..WalletMeta::new(Origin::WatchOnly)
+        },
+    };
+    let mut batch = StoreBatch::default();
+    replace_wallet_record(
+        &mut batch,
+        name,
+        &get_watch_key(name),
+        &serde_json::to_vec(&stored)?,
+    );
+    db.write(batch)?;
+
+    Ok(())
+}
+
+pub fn watch_wallet_exists(db: &dyn WalletStore, name: &str) -> Result<bool, ToolkitError> {
+    Ok(db.get(&get_watch_key(name))?.is_some())
+}
+
+pub fn read_watch_meta(
+    db: &dyn WalletStore,
+    name: &str,
+) -> Result<Option<WalletMeta>, ToolkitError> {
+    Ok(read_stored_watch(db, name)?.map(|stored| stored.meta))
+}
+
+pub fn update_watch_meta(
+    db: &dyn WalletStore,
+    name: &str,
+    update: impl FnOnce(&mut WalletMeta),
+) -> Result<(), ToolkitError> {
+    let mut stored = read_stored_watch(db, name)?
+        .ok_or_else(|| ToolkitError::NotFound(format!("Wallet {} not found", name)))?;
+
+    update(&mut stored.meta);
+    db.put(&get_watch_key(name), &serde_json::to_vec(&stored)?)?;
+
+    Ok(())
+}
+
+/// Every watch-only wallet as its name, pubkey and metadata.
+pub fn list_watch_wallets(
+    db: &dyn WalletStore,
+) -> Result<Vec<(String, String, WalletMeta)>, ToolkitError> {
+    let prefix = "watch:";
+    let mut wallets = Vec::new();
+
+    for (key, value) in db.scan(prefix)? {
+        let name = key[prefix.len()..].to_string();
+        match serde_json::from_slice::<StoredWatch>(&value) {
+            Ok(stored) => wallets.push((name, stored.pubkey, stored.meta)),
+            Err(e) => eprintln!(
+                "Skipping undecodable watch-only wallet {} ({}), run `db check`",
+                name, e
+            ),
+        }
+    }
+
+    Ok(wallets)
+}
+
+pub fn validate_watch_record(value: &[u8]) -> Result<(), ToolkitError> {
+    let stored: StoredWatch = serde_json::from_slice(value)
+        .map_err(|e| ToolkitError::Decode(format!("undecodable watch-only record: {}", e)))?;
+    parse_pubkey(&stored.pubkey)?;
+
+    Ok(())
+}
+
+/// Adds a contact, refusing to replace an existing one unless `force` is set.
+pub fn add_contact(
+    db: &dyn WalletStore,
+    name: &str,
+    pubkey: &str,
+    description: Option<String>,
+    force: bool,
+) -> Result<(), ToolkitError> {
+    let pubkey = parse_pubkey(pubkey)?;
+    check_name("Contact", name)?;
+    let key = get_contact_key(name);
+
+    if !force && db.get(&key)?.is_some() {
+        return Err(ToolkitError::AlreadyExists(format!(
+            "Contact {} already exists, pass --force to overwrite it",
+            name
+        )));
+    }
+
+    let contact = Contact {
+        pubkey: pubkey.to_string(),
+        description,
+    };
+    db.put(&key, &serde_json::to_vec(&contact)?)?;
+
+    Ok(())
+}
+
+pub fn read_contact(db: &dyn WalletStore, name: &str) -> Result<Option<Contact>, ToolkitError> {
+    match db.get(&get_contact_key(name))? {
+        Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+        None => Ok(None),
+    }
+}
+
+pub fn remove_contact(db: &dyn WalletStore, name: &str) -> Result<(), ToolkitError> {
+    let key = get_contact_key(name);
+    if db.get(&key)?.is_none() {
+        return Err(ToolkitError::NotFound(format!(
+            "Contact {} not found",
+            name
+        )));
+    }
+
+    db.delete(&key)
+}
+
+pub fn list_contacts(db: &dyn WalletStore) -> Result<Vec<(String, Contact)>, ToolkitError> {
+    let prefix = "contact:";
+    let mut contacts = Vec::new();
+
+    for (key, value) in db.scan(prefix)? {
+        let name = key[prefix.len()..].to_string();
+        match serde_json::from_slice(&value) {
+            Ok(contact) => contacts.push((name, contact)),
+            Err(e) => eprintln!(
+                "Skipping undecodable contact {} ({}), run `db check`",
+                name, e
+            ),
+        }
+    }
+
+    Ok(contacts)
+}
+
+pub fn validate_contact_record(value: &[u8]) -> Result<(), ToolkitError> {
+    let contact: Contact = serde_json::from_slice(value)
+        .map_err(|e| ToolkitError::Decode(format!("undecodable contact record: {}", e)))?;
+    parse_pubkey(&contact.pubkey)?;
+
+    Ok(())
+}
+
+/// The pubkey of the wallet called `name`: a plain wallet, a seed child or a watch-only
+/// wallet. Seed children are re-derived, so they need the passphrase.
+fn wallet_pubkey(
+    db: &dyn WalletStore,
+    name: &str,
+    passphrase: &(impl PassphraseSource + ?Sized),
+) -> Result<Option<String>, ToolkitError> {
+    if let Some(pubkey) = read_wallet_pubkey(db, name)? {
+        return Ok(Some(pubkey));
+    }
+    if let Some(stored) = read_stored_watch(db, name)? {
+        return Ok(Some(stored.pubkey));
+    }
+
+    match parse_child_name(name) {
+        Some((parent, index)) => {
+            Ok(read_child_wallet(db, parent, index, passphrase)?.map(|wallet| wallet.pubkey))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Turns whatever was given where a pubkey is expected into a pubkey: a base58 pubkey,
+/// or the name of a wallet, watch-only wallet or contact. `wallet:`, `watch:` and
+/// `contact:` prefixes pick one of them explicitly when a name is ambiguous.
+pub fn resolve_address(
+    db: &dyn WalletStore,
+    input: &str,
+    passphrase: &(impl PassphraseSource + ?Sized),
+) -> Result<Pubkey, ToolkitError> {
+    if let Ok(pubkey) = Pubkey::from_str(input) {
+        return Ok(pubkey);
+    }
+
+    let explicit = match input.split_once(':') {
+        Some(("wallet", name)) => Some(wallet_pubkey(db, name, passphrase)?),
+        Some(("watch", name)) => Some(read_stored_watch(db, name)?.map(|stored| stored.pubkey)),
+        Some(("contact", name)) => Some(read_contact(db, name)?.map(|contact| contact.pubkey)),
+        _ => None,
+    };
+    if let Some(pubkey) = explicit {
+        let pubkey =
+            pubkey.ok_or_else(|| ToolkitError::NotFound(format!("Unknown address {}", input)))?;
+        return parse_pubkey(&pubkey);
+    }
+
+    let wallet = wallet_pubkey(db, input, passphrase)?;
+    let contact = read_contact(db, input)?.map(|contact| contact.pubkey);
+
+    let pubkey = match (wallet, contact) {
+        (Some(wallet), Some(contact)) if wallet != contact => {
+            return Err(ToolkitError::InvalidInput(format!(
+                "{} is ambiguous: wallet {} and contact {} differ, use wallet:{} or contact:{}",
+                input, wallet, contact, input, input
+            )));
+        }
+        (Some(pubkey), _) | (None, Some(pubkey)) => pubkey,
+        (None, None) => {
+            return Err(ToolkitError::NotFound(format!(
+                "Unknown address {}: not a valid pubkey, nor a wallet, watch-only wallet or contact",
+                input
+            )));
+        }
+    };
+
+    parse_pubkey(&pubkey)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::meta::{SortKey, WalletFilter};
+    use crate::utils::store::MemoryStore;
+    use crate::utils::wallet::{
+        generate_keypair, list_wallet_entries, read_wallet, read_wallet_meta,
+    };
+    use solana_sdk::{signature::Keypair, signer::Signer};
+
+    const PASSPHRASE: &str = "test passphrase";
+    const WBA_PUBKEY: &str = "Be9MdYwSsMUTLCA3pV9FaVsPDSJyuokjeNZLoaU13s1W";
+
+    #[test]
+    fn test_watch_only_wallets() {
+        let db = MemoryStore::default();
+
+        add_watch_wallet(&db, "wba", WBA_PUBKEY, Some("WBA vault".to_string()), false).unwrap();
+        assert!(add_watch_wallet(&db, "wba", WBA_PUBKEY, None, false).is_err());
+        assert!(add_watch_wallet(&db, "bad", "not a pubkey", None, false).is_err());
+
+        let meta = read_wallet_meta(&db, "wba").unwrap().unwrap();
+        assert_eq!(meta.origin, Origin::WatchOnly);
+
+        let entries =
+            list_wallet_entries(&db, PASSPHRASE, &WalletFilter::default(), SortKey::Name).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].pubkey.as_deref(), Some(WBA_PUBKEY));
+
+        // Watch-only names are taken like any wallet name, but hold nothing to sign with
+        assert!(ensure_name_available(&db, "wba", false).is_err());
+        assert!(matches!(
+            read_wallet(&db, "wba", PASSPHRASE),
+            Err(ToolkitError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_contacts() {
+        let db = MemoryStore::default();
+
+        add_contact(&db, "teammate", WBA_PUBKEY, None, false).unwrap();
+        assert!(add_contact(&db, "teammate", WBA_PUBKEY, None, false).is_err());
+        assert_eq!(list_contacts(&db).unwrap().len(), 1);
+
+        remove_contact(&db, "teammate").unwrap();
+        assert!(remove_contact(&db, "teammate").is_err());
+        assert!(list_contacts(&db).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_resolve_address() {
+        let db = MemoryStore::default();
+        generate_keypair(&db, "payer", PASSPHRASE, false).unwrap();
+        let payer = read_wallet_pubkey(&db, "payer").unwrap().unwrap();
+        add_watch_wallet(&db, "wba", WBA_PUBKEY, None, false).unwrap();
+
+        let resolve = |input: &str| resolve_address(&db, input, PASSPHRASE).map(|p| p.to_string());
+
+        assert_eq!(resolve(WBA_PUBKEY).unwrap(), WBA_PUBKEY);
+        assert_eq!(resolve("payer").unwrap(), payer);
+        assert_eq!(resolve("wba").unwrap(), WBA_PUBKEY);
+        assert!(resolve("nobody")
+            .unwrap_err()
+            .to_string()
+            .contains("Unknown address"));
+
+        // A contact with the same pubkey as a wallet is not ambiguous
+        add_contact(&db, "wba", WBA_PUBKEY, None, false).unwrap();
+        assert_eq!(resolve("wba").unwrap(), WBA_PUBKEY);
+
+        let other = Keypair::new().pubkey().to_string();
+        add_contact(&db, "payer", &other, None, false).unwrap();
+        assert!(resolve("payer")
+            .unwrap_err()
+            .to_string()
+            .contains("ambiguous"));
+        assert_eq!(resolve("wallet:payer").unwrap(), payer);
+        assert_eq!(resolve("contact:payer").unwrap(), other);
+        assert!(resolve("watch:payer").is_err());
+
+        // Names read as a prefix could never be resolved, so they are refused
+        for name in ["wallet:ops", "ops:cold"] {
+            assert!(matches!(
+                generate_keypair(&db, name, PASSPHRASE, false),
+                Err(ToolkitError::InvalidInput(_))
+            ));
+            assert!(add_watch_wallet(&db, name, WBA_PUBKEY, None, false).is_err());
+        }
+        assert!(matches!(
+            add_contact(&db, "contact:bob", WBA_PUBKEY, None, false),
+            Err(ToolkitError::InvalidInput(_))
+        ));
+    }
+}
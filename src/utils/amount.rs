@@ -0,0 +1,158 @@
+use std::str::FromStr;
+
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+
+use super::error::ToolkitError;
+
+/// Decimals of a SOL amount: 1 lamport is 0.000000001 SOL.
+const SOL_DECIMALS: usize = 9;
+
+/// An amount to send, as typed on the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Amount {
+    Lamports(u64),
+    /// Everything the wallet holds, minus the fee when it pays it
+    All,
+}
+
+/// `1.5`, `1.5SOL`, `2SOL` and `0.1 sol` are SOL, `100000lamports` is lamports, `ALL` is the
+/// whole balance. A whole number without a unit is refused: it used to mean lamports, and
+/// reading it as SOL would send a billion times as much.
+impl FromStr for Amount {
+    type Err = ToolkitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let input = s.trim().to_lowercase();
+
+        if input == "all" {
+            return Ok(Amount::All);
+        }
+        if let Some(lamports) = input.strip_suffix("lamports") {
+            let lamports = lamports.trim();
+            if lamports.is_empty() || !lamports.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(invalid(s, "lamports must be a whole number"));
+            }
+            let lamports = lamports
+                .parse()
+                .map_err(|_| invalid(s, "too many lamports"))?;
+            return nonzero(s, lamports);
+        }
+
+        let sol = match input.strip_suffix("sol") {
+            Some(sol) => sol.trim(),
+            None if !input.is_empty() && input.bytes().all(|b| b.is_ascii_digit()) => {
+                return Err(invalid(
+                    s,
+                    "a whole number needs a unit, like 2SOL or 100000lamports",
+                ));
+            }
+            None => &input,
+        };
+        let lamports = parse_sol(sol).map_err(|reason| invalid(s, reason))?;
+        nonzero(s, lamports)
+    }
+}
+
+fn nonzero(input: &str, lamports: u64) -> Result<Amount, ToolkitError> {
+    match lamports {
+        0 => Err(invalid(input, "nothing to send")),
+        lamports => Ok(Amount::Lamports(lamports)),
+    }
+}
+
+fn invalid(input: &str, reason: &str) -> ToolkitError {
+    ToolkitError::InvalidInput(format!("Invalid amount {}: {}", input, reason))
+}
+
+/// Exact decimal SOL to lamports, using integers only.
+fn parse_sol(sol: &str) -> Result<u64, &'static str> {
+    let (whole, fraction) = sol.split_once('.').unwrap_or((sol, ""));
+
+    let is_number = |digits: &str| digits.bytes().all(|b| b.is_ascii_digit());
+    if whole.is_empty() || !is_number(whole) || !is_number(fraction) {
+        return Err("expected a number of SOL like 0.5, or lamports like 500lamports");
+    }
+    if sol.ends_with('.') {
+        return Err("expected digits after the decimal point");
+    }
+    if fraction.len() > SOL_DECIMALS {
+        return Err("SOL has at most 9 decimals, 0.000000001 SOL is 1 lamport");
+    }
+
+    let fraction = format!("{:0<width$}", fraction, width = SOL_DECIMALS);
+    let whole: u64 = whole.parse().map_err(|_| "too many SOL")?;
+    let fraction: u64 = fraction.parse().map_err(|_| "too many SOL")?;
+
+    whole
+        .checked_mul(LAMPORTS_PER_SOL)
+        .and_then(|lamports| lamports.checked_add(fraction))
+        .ok_or("too many SOL")
+}
+
+/// Lamports as SOL, exactly: no floating point, trailing zeros trimmed.
+pub fn format_sol(lamports: u64) -> String {
+    let whole = lamports / LAMPORTS_PER_SOL;
+    let fraction = lamports % LAMPORTS_PER_SOL;
+
+    if fraction == 0 {
+        return whole.to_string();
+    }
+
+    let fraction = format!("{:09}", fraction);
+    format!("{}.{}", whole, fraction.trim_end_matches('0'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_amount() {
+        let lamports = |s: &str| s.parse::<Amount>().unwrap();
+
+        assert_eq!(lamports("0.1"), Amount::Lamports(100_000_000));
+        assert_eq!(lamports("1.5SOL"), Amount::Lamports(1_500_000_000));
+        assert_eq!(lamports("2 sol"), Amount::Lamports(2_000_000_000));
+        assert_eq!(lamports("2.0"), Amount::Lamports(2_000_000_000));
+        assert_eq!(lamports("0.000000001"), Amount::Lamports(1));
+        assert_eq!(lamports("100000lamports"), Amount::Lamports(100_000));
+        assert_eq!(lamports("ALL"), Amount::All);
+
+        for bad in [
+            "",
+            "0",
+            "0lamports",
+            "0.0000000001",
+            "1.",
+            ".5",
+            "-1",
+            "1e9",
+            "1,5",
+            "0.5lamports",
+            "18446744074",
+            "lamports",
+        ] {
+            let error = bad.parse::<Amount>().unwrap_err();
+            assert_eq!(error.exit_code(), 12, "{}", bad);
+        }
+
+        let error = "0.0000000001".parse::<Amount>().unwrap_err();
+        assert!(error.to_string().contains("at most 9 decimals"));
+
+        // Once lamports, a bare whole number is neither lamports nor SOL now
+        let error = "100000".parse::<Amount>().unwrap_err();
+        assert_eq!(error.exit_code(), 12);
+        assert!(error.to_string().contains("needs a unit"));
+        assert!(" 5 ".parse::<Amount>().is_err());
+    }
+
+    #[test]
+    fn test_format_sol() {
+        assert_eq!(format_sol(0), "0");
+        assert_eq!(format_sol(2_000_000_000), "2");
+        assert_eq!(format_sol(1_500_000_000), "1.5");
+        assert_eq!(format_sol(5_000), "0.000005");
+        assert_eq!(format_sol(1), "0.000000001");
+        assert_eq!(format_sol(u64::MAX), "18446744073.709551615");
+    }
+}
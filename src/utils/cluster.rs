@@ -0,0 +1,261 @@
+use std::{fmt, str::FromStr};
+
+use clap::ValueEnum;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use solana_sdk::{commitment_config::CommitmentConfig, hash::Hash, signature::Signature};
+
+use super::error::ToolkitError;
+
+const DEVNET_URL: &str = "https://api.devnet.solana.com";
+const TESTNET_URL: &str = "https://api.testnet.solana.com";
+const MAINNET_BETA_URL: &str = "https://api.mainnet-beta.solana.com";
+const LOCALNET_URL: &str = "http://localhost:8899";
+
+/// Mainnet-beta's genesis hash, which gives it away behind any RPC URL.
+pub const MAINNET_BETA_GENESIS_HASH: &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+
+/// A Solana cluster, given by moniker or by RPC URL.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Cluster {
+    #[default]
+    Devnet,
+    Testnet,
+    MainnetBeta,
+    /// A local test validator on its default port
+    Localnet,
+    /// Any other RPC endpoint
+    Custom(String),
+}
+
+impl Cluster {
+    pub fn url(&self) -> &str {
+        match self {
+            Cluster::Devnet => DEVNET_URL,
+            Cluster::Testnet => TESTNET_URL,
+            Cluster::MainnetBeta => MAINNET_BETA_URL,
+            Cluster::Localnet => LOCALNET_URL,
+            Cluster::Custom(url) => url,
+        }
+    }
+
+    /// Whether the cluster runs a faucet, which every cluster but mainnet-beta does. A
+    /// custom endpoint is told apart by its genesis hash, only fetched for those.
+    pub fn has_faucet(
+        &self,
+        genesis_hash: impl FnOnce() -> Result<Hash, ToolkitError>,
+    ) -> Result<bool, ToolkitError> {
+        match self {
+            Cluster::MainnetBeta => Ok(false),
+            Cluster::Custom(_) => Ok(genesis_hash()?.to_string() != MAINNET_BETA_GENESIS_HASH),
+            _ => Ok(true),
+        }
+    }
+}
+
+impl fmt::Display for Cluster {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cluster::Devnet => write!(f, "devnet"),
+            Cluster::Testnet => write!(f, "testnet"),
+            Cluster::MainnetBeta => write!(f, "mainnet-beta"),
+            Cluster::Localnet => write!(f, "localnet"),
+            Cluster::Custom(url) => write!(f, "{}", url),
+        }
+    }
+}
+
+/// Monikers, or an `http(s)://` URL. The public endpoints and the local validator are
+/// recognised by URL too, so explorer links come out the same either way.
+impl FromStr for Cluster {
+    type Err = ToolkitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cluster = match s.trim_end_matches('/') {
+            "devnet" | DEVNET_URL => Cluster::Devnet,
+            "testnet" | TESTNET_URL => Cluster::Testnet,
+            "mainnet-beta" | "mainnet" | MAINNET_BETA_URL => Cluster::MainnetBeta,
+            "localnet" | "localhost" | LOCALNET_URL | "http://127.0.0.1:8899" => Cluster::Localnet,
+            url if url.starts_with("http://") || url.starts_with("https://") => {
+                Cluster::Custom(url.to_string())
+            }
+            other => {
+                return Err(ToolkitError::InvalidInput(format!(
+                    "Unknown cluster {}, expected a moniker or an http(s) URL",
+                    other
+                )))
+            }
+        };
+
+        Ok(cluster)
+    }
+}
+
+/// Stored as its moniker or URL, like on the command line.
+impl Serialize for Cluster {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Cluster {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// How settled a transaction has to be before a command reports it.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Commitment {
+    /// Seen by the node we talk to
+    Processed,
+    /// Voted on by a supermajority of the cluster
+    Confirmed,
+    /// Rooted, it can no longer be rolled back
+    #[default]
+    Finalized,
+}
+
+impl From<Commitment> for CommitmentConfig {
+    fn from(commitment: Commitment) -> Self {
+        match commitment {
+            Commitment::Processed => CommitmentConfig::processed(),
+            Commitment::Confirmed => CommitmentConfig::confirmed(),
+            Commitment::Finalized => CommitmentConfig::finalized(),
+        }
+    }
+}
+
+/// Block explorer used for the links printed after a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Explorer {
+    /// explorer.solana.com
+    #[default]
+    Solana,
+    /// solscan.io
+    Solscan,
+    /// solana.fm
+    #[value(name = "solanafm")]
+    SolanaFm,
+}
+
+impl Explorer {
+    pub fn tx_url(&self, cluster: &Cluster, signature: &Signature) -> String {
+        let base = match self {
+            Explorer::Solana => "https://explorer.solana.com",
+            Explorer::Solscan => "https://solscan.io",
+            Explorer::SolanaFm => "https://solana.fm",
+        };
+
+        match self.cluster_query(cluster) {
+            Some(query) => format!("{}/tx/{}?{}", base, signature, query),
+            None => format!("{}/tx/{}", base, signature),
+        }
+    }
+
+    /// Query string selecting `cluster`, none for mainnet which every explorer defaults to.
+    /// Local and custom endpoints are passed as a custom RPC URL for the browser to query.
+    fn cluster_query(&self, cluster: &Cluster) -> Option<String> {
+        let custom = || format!("cluster=custom&customUrl={}", percent_encode(cluster.url()));
+
+        match (self, cluster) {
+            (_, Cluster::MainnetBeta) => None,
+            (Explorer::SolanaFm, Cluster::Devnet) => Some("cluster=devnet-solana".to_string()),
+            (Explorer::SolanaFm, Cluster::Testnet) => Some("cluster=testnet-solana".to_string()),
+            (Explorer::SolanaFm, Cluster::Localnet) => Some("cluster=localnet-solana".to_string()),
+            (_, Cluster::Devnet) => Some("cluster=devnet".to_string()),
+            (_, Cluster::Testnet) => Some("cluster=testnet".to_string()),
+            (_, Cluster::Localnet | Cluster::Custom(_)) => Some(custom()),
+        }
+    }
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cluster() {
+        assert_eq!("devnet".parse::<Cluster>().unwrap(), Cluster::Devnet);
+        assert_eq!(
+            "https://api.mainnet-beta.solana.com/"
+                .parse::<Cluster>()
+                .unwrap(),
+            Cluster::MainnetBeta
+        );
+        assert_eq!(
+            "http://127.0.0.1:8899".parse::<Cluster>().unwrap(),
+            Cluster::Localnet
+        );
+        assert_eq!(
+            "https://rpc.example.com".parse::<Cluster>().unwrap(),
+            Cluster::Custom("https://rpc.example.com".to_string())
+        );
+        assert_eq!(Cluster::Testnet.url(), TESTNET_URL);
+        assert_eq!(Cluster::MainnetBeta.to_string(), "mainnet-beta");
+
+        let error = "moonnet".parse::<Cluster>().unwrap_err();
+        assert_eq!(error.exit_code(), 12);
+    }
+
+    #[test]
+    fn test_has_faucet() {
+        let mainnet = || Ok(MAINNET_BETA_GENESIS_HASH.parse::<Hash>().unwrap());
+        let unreachable = || Err(ToolkitError::Rpc("not asked".to_string()));
+        let custom = Cluster::Custom("https://rpc.example.com".to_string());
+
+        assert!(!Cluster::MainnetBeta.has_faucet(unreachable).unwrap());
+        assert!(Cluster::Devnet.has_faucet(unreachable).unwrap());
+        assert!(!custom.has_faucet(mainnet).unwrap());
+        assert!(custom.has_faucet(|| Ok(Hash::default())).unwrap());
+        assert!(custom.has_faucet(unreachable).is_err());
+    }
+
+    #[test]
+    fn test_explorer_links() {
+        let signature = Signature::default();
+
+        assert_eq!(
+            Explorer::Solana.tx_url(&Cluster::Devnet, &signature),
+            format!(
+                "https://explorer.solana.com/tx/{}?cluster=devnet",
+                signature
+            )
+        );
+        assert_eq!(
+            Explorer::Solscan.tx_url(&Cluster::MainnetBeta, &signature),
+            format!("https://solscan.io/tx/{}", signature)
+        );
+        assert_eq!(
+            Explorer::SolanaFm.tx_url(&Cluster::Testnet, &signature),
+            format!("https://solana.fm/tx/{}?cluster=testnet-solana", signature)
+        );
+        assert_eq!(
+            Explorer::Solana.tx_url(&Cluster::Localnet, &signature),
+            format!(
+                "https://explorer.solana.com/tx/{}?cluster=custom&customUrl=http%3A%2F%2Flocalhost%3A8899",
+                signature
+            )
+        );
+        assert!(Explorer::Solscan
+            .tx_url(
+                &Cluster::Custom("https://rpc.example.com".to_string()),
+                &signature
+            )
+            .ends_with("?cluster=custom&customUrl=https%3A%2F%2Frpc.example.com"));
+    }
+}
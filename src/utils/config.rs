@@ -0,0 +1,234 @@
+use std::{collections::BTreeMap, env, fmt, fs, io, path::Path, path::PathBuf};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use super::cluster::{Cluster, Commitment};
+use super::error::ToolkitError;
+use super::output::OutputFormat;
+
+/// Profile used when neither `--profile` nor `active_profile` name one.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Settings a profile can hold. Each one stands in for the global option of the same name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ConfigKey {
+    DbPath,
+    Cluster,
+    Commitment,
+    FeePayer,
+    Output,
+}
+
+impl fmt::Display for ConfigKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", value_name(self))
+    }
+}
+
+/// A named set of defaults for the global options.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct Profile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub db_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cluster: Option<Cluster>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commitment: Option<Commitment>,
+    /// Name of the wallet paying transaction fees
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_payer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<OutputFormat>,
+}
+
+impl Profile {
+    pub fn get(&self, key: ConfigKey) -> Option<String> {
+        match key {
+            ConfigKey::DbPath => self.db_path.clone(),
+            ConfigKey::Cluster => self.cluster.as_ref().map(Cluster::to_string),
+            ConfigKey::Commitment => self.commitment.map(|c| value_name(&c)),
+            ConfigKey::FeePayer => self.fee_payer.clone(),
+            ConfigKey::Output => self.output.map(|o| value_name(&o)),
+        }
+    }
+
+    /// Sets `key` from its command line form, an empty value clears it.
+    pub fn set(&mut self, key: ConfigKey, value: &str) -> Result<(), ToolkitError> {
+        let value = Some(value).filter(|v| !v.is_empty());
+
+        match key {
+            ConfigKey::DbPath => self.db_path = value.map(str::to_string),
+            ConfigKey::Cluster => self.cluster = value.map(str::parse::<Cluster>).transpose()?,
+            ConfigKey::Commitment => self.commitment = value.map(parse_value).transpose()?,
+            ConfigKey::FeePayer => self.fee_payer = value.map(str::to_string),
+            ConfigKey::Output => self.output = value.map(parse_value).transpose()?,
+        }
+
+        Ok(())
+    }
+}
+
+/// The config file: every profile, and which one is in use.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct Config {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/wba_toolkit/config.toml`, falling back to `~/.config`.
+    pub fn default_path() -> Option<PathBuf> {
+        let config_home = env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+        Some(config_home.join("wba_toolkit").join("config.toml"))
+    }
+
+    /// A missing file is an empty config.
+    pub fn load(path: &Path) -> Result<Config, ToolkitError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => toml::from_str(&contents).map_err(|e| {
+                ToolkitError::Decode(format!("Invalid config file {}: {}", path.display(), e))
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ToolkitError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_toml()?)?;
+
+        Ok(())
+    }
+
+    pub fn to_toml(&self) -> Result<String, ToolkitError> {
+        toml::to_string_pretty(self)
+            .map_err(|e| ToolkitError::Decode(format!("Could not encode config: {}", e)))
+    }
+
+    /// `name`, else the active profile, else `default`.
+    pub fn profile_name<'a>(&'a self, name: Option<&'a str>) -> &'a str {
+        name.or(self.active_profile.as_deref())
+            .unwrap_or(DEFAULT_PROFILE)
+    }
+
+    /// The profile in use. Naming a profile that does not exist is an error, the implicit
+    /// `default` one is empty until something is set in it.
+    pub fn profile(&self, name: Option<&str>) -> Result<Profile, ToolkitError> {
+        let profile_name = self.profile_name(name);
+
+        match self.profiles.get(profile_name) {
+            Some(profile) => Ok(profile.clone()),
+            None if profile_name == DEFAULT_PROFILE => Ok(Profile::default()),
+            None => Err(ToolkitError::NotFound(format!(
+                "Profile {} not found",
+                profile_name
+            ))),
+        }
+    }
+
+    /// The profile `config set` writes to, created on first use.
+    pub fn profile_mut(&mut self, name: Option<&str>) -> &mut Profile {
+        let profile_name = self.profile_name(name).to_string();
+
+        self.profiles.entry(profile_name).or_default()
+    }
+
+    pub fn use_profile(&mut self, name: &str) -> Result<(), ToolkitError> {
+        if !self.profiles.contains_key(name) {
+            return Err(ToolkitError::NotFound(format!(
+                "Profile {} not found",
+                name
+            )));
+        }
+        self.active_profile = Some(name.to_string());
+
+        Ok(())
+    }
+}
+
+fn value_name<T: ValueEnum>(value: &T) -> String {
+    value
+        .to_possible_value()
+        .map(|v| v.get_name().to_string())
+        .unwrap_or_default()
+}
+
+fn parse_value<T: ValueEnum>(value: &str) -> Result<T, ToolkitError> {
+    T::from_str(value, true).map_err(ToolkitError::InvalidInput)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_config_round_trip() {
+        let tmp_dir = TempDir::new("config").unwrap();
+        let path = tmp_dir.path().join("wba_toolkit").join("config.toml");
+
+        let mut config = Config::load(&path).unwrap();
+        assert_eq!(config, Config::default());
+
+        let localnet = config.profile_mut(Some("localnet"));
+        localnet.set(ConfigKey::Cluster, "localnet").unwrap();
+        localnet.set(ConfigKey::Commitment, "confirmed").unwrap();
+        localnet.set(ConfigKey::Output, "json").unwrap();
+        localnet.set(ConfigKey::FeePayer, "payer").unwrap();
+        config.use_profile("localnet").unwrap();
+        config.save(&path).unwrap();
+
+        let config = Config::load(&path).unwrap();
+        let profile = config.profile(None).unwrap();
+        assert_eq!(profile.cluster, Some(Cluster::Localnet));
+        assert_eq!(
+            profile.get(ConfigKey::Commitment).as_deref(),
+            Some("confirmed")
+        );
+        assert_eq!(profile.output, Some(OutputFormat::Json));
+        assert_eq!(profile.get(ConfigKey::DbPath), None);
+
+        assert!(fs::read_to_string(&path)
+            .unwrap()
+            .contains("[profiles.localnet]"));
+    }
+
+    #[test]
+    fn test_profile_errors() {
+        let mut config = Config::default();
+        assert_eq!(config.profile(None).unwrap(), Profile::default());
+        assert_eq!(config.profile(Some("mainnet")).unwrap_err().exit_code(), 10);
+        assert_eq!(config.use_profile("mainnet").unwrap_err().exit_code(), 10);
+
+        let profile = config.profile_mut(None);
+        assert_eq!(
+            profile
+                .set(ConfigKey::Output, "yaml")
+                .unwrap_err()
+                .exit_code(),
+            12
+        );
+        assert_eq!(
+            profile
+                .set(ConfigKey::Cluster, "moonnet")
+                .unwrap_err()
+                .exit_code(),
+            12
+        );
+
+        profile.set(ConfigKey::DbPath, "wallets").unwrap();
+        profile.set(ConfigKey::DbPath, "").unwrap();
+        assert_eq!(profile.db_path, None);
+    }
+}
@@ -0,0 +1,173 @@
+use std::{fmt, ops::Deref};
+
+use argon2::Argon2;
+use chacha20poly1305::{
+    aead::{Aead, KeyInit},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::Zeroizing;
+
+use super::error::ToolkitError;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// Secret key or seed bytes held in memory. They are wiped when dropped and never
+/// show up in `Debug` output; serialized, they are the plain byte array.
+#[derive(Clone, PartialEq, Eq, Default)]
+pub struct SecretBytes(Zeroizing<Vec<u8>>);
+
+impl From<Vec<u8>> for SecretBytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        SecretBytes(Zeroizing::new(bytes))
+    }
+}
+
+impl Deref for SecretBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for SecretBytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SecretBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<redacted {} bytes>", self.0.len())
+    }
+}
+
+impl Serialize for SecretBytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretBytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::<u8>::deserialize(deserializer).map(SecretBytes::from)
+    }
+}
+
+/// Where the passphrase of the wallet store comes from. Reads that only sometimes open a
+/// secret take one, so a command that never decrypts anything never asks for it.
+pub trait PassphraseSource {
+    fn passphrase(&self) -> Result<String, ToolkitError>;
+}
+
+impl PassphraseSource for str {
+    fn passphrase(&self) -> Result<String, ToolkitError> {
+        Ok(self.to_string())
+    }
+}
+
+/// A secret sealed with a key derived from a passphrase.
+///
+/// The key is derived with Argon2id from the passphrase and a random salt, and
+/// the secret is sealed with ChaCha20-Poly1305 so tampering is detected on open.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EncryptedSecret {
+    pub salt: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key, ToolkitError> {
+    let mut key = Key::default();
+
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| {
+            ToolkitError::Crypto(format!("Failed to derive key from passphrase: {}", e))
+        })?;
+
+    Ok(key)
+}
+
+pub fn encrypt_secret(secret: &[u8], passphrase: &str) -> Result<EncryptedSecret, ToolkitError> {
+    // Fresh salt and nonce for every write
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let key = derive_key(passphrase, &salt)?;
+    let cipher = ChaCha20Poly1305::new(&key);
+
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), secret)
+        .map_err(|_| ToolkitError::Crypto("Failed to encrypt secret".to_string()))?;
+
+    Ok(EncryptedSecret {
+        salt: salt.to_vec(),
+        nonce: nonce.to_vec(),
+        ciphertext,
+    })
+}
+
+pub fn decrypt_secret(
+    encrypted: &EncryptedSecret,
+    passphrase: &str,
+) -> Result<SecretBytes, ToolkitError> {
+    if encrypted.nonce.len() != NONCE_LEN {
+        return Err(ToolkitError::Decode(
+            "Invalid nonce length in encrypted secret".to_string(),
+        ));
+    }
+
+    let key = derive_key(passphrase, &encrypted.salt)?;
+    let cipher = ChaCha20Poly1305::new(&key);
+
+    let secret = cipher
+        .decrypt(
+            Nonce::from_slice(&encrypted.nonce),
+            encrypted.ciphertext.as_ref(),
+        )
+        .map_err(|_| ToolkitError::Crypto("Wrong passphrase or corrupted wallet".to_string()))?;
+
+    Ok(secret.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_and_decrypt_secret() {
+        let secret = [7u8; 64];
+
+        let encrypted = encrypt_secret(&secret, "correct horse").unwrap();
+        assert_ne!(encrypted.ciphertext, secret.to_vec());
+
+        let decrypted = decrypt_secret(&encrypted, "correct horse").unwrap();
+        assert_eq!(decrypted[..], secret[..]);
+    }
+
+    #[test]
+    fn test_secret_bytes_are_redacted() {
+        let secret = SecretBytes::from(vec![42u8; 64]);
+
+        assert_eq!(format!("{:?}", secret), "<redacted 64 bytes>");
+        assert!(!format!("{:?}", secret).contains("42"));
+
+        // Serialized secrets keep the keypair file layout
+        let json = serde_json::to_string(&secret).unwrap();
+        assert_eq!(json, serde_json::to_string(&vec![42u8; 64]).unwrap());
+        assert_eq!(serde_json::from_str::<SecretBytes>(&json).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_decrypt_with_wrong_passphrase() {
+        let encrypted = encrypt_secret(&[1u8; 64], "correct horse").unwrap();
+
+        assert!(decrypt_secret(&encrypted, "battery staple").is_err());
+    }
+}
@@ -0,0 +1,354 @@
+use super::address::{validate_contact_record, validate_watch_record};
+use super::error::ToolkitError;
+use super::seed::validate_seed_record;
+use super::store::{StoreBatch, WalletStore};
+use super::wallet::{
+    add_record_metadata, encrypt_plaintext_record, is_plaintext_record, validate_wallet_record,
+    verify_passphrase,
+};
+
+/// Version of the database layout as a whole.
+/// 0: plaintext wallet records, 1: encrypted secrets, 2: versioned records with metadata.
+pub const SCHEMA_VERSION: u32 = 2;
+
+const SCHEMA_VERSION_KEY: &str = "meta:schema_version";
+
+/// Record prefixes whose values go through the migrations, live or trashed.
+const MIGRATED_PREFIXES: [&str; 2] = ["wallet:", "trash:wallet:"];
+
+/// Upgrades one raw record, returning `None` when it is already up to date. Upgrades that
+/// seal secrets fail without a passphrase.
+type Upgrade = fn(&[u8], Option<&str>) -> Result<Option<Vec<u8>>, ToolkitError>;
+
+struct Migration {
+    /// Schema version the database is at once this migration has run
+    version: u32,
+    description: &'static str,
+    upgrade: Upgrade,
+}
+
+/// Every migration in order. Upgrades must be idempotent: a database without a
+/// version marker runs all of them, whatever its records actually look like.
+const MIGRATIONS: [Migration; 2] = [
+    Migration {
+        version: 1,
+        description: "encrypt plaintext wallet secrets",
+        upgrade: encrypt_plaintext_record,
+    },
+    Migration {
+        version: 2,
+        description: "add record versions and wallet metadata",
+        upgrade: |value, _| add_record_metadata(value),
+    },
+];
+
+/// What a migration run did, or would do on a dry run.
+#[derive(Debug, Default)]
+pub struct MigrationReport {
+    pub from: u32,
+    pub to: u32,
+    /// Descriptions of the migrations that were applied
+    pub applied: Vec<&'static str>,
+    pub upgraded: Vec<String>,
+    /// Records that could not be upgraded, with the reason. Nothing is written if any fail.
+    pub failed: Vec<(String, String)>,
+}
+
+/// A record `db check` found something wrong with.
+#[derive(Debug)]
+pub struct Issue {
+    pub key: String,
+    pub problem: String,
+}
+
+pub fn read_schema_version(db: &dyn WalletStore) -> Result<Option<u32>, ToolkitError> {
+    match db.get(SCHEMA_VERSION_KEY)? {
+        Some(value) => {
+            let version = String::from_utf8(value)?
+                .parse()
+                .map_err(|e| ToolkitError::Decode(format!("Invalid schema version: {}", e)))?;
+            Ok(Some(version))
+        }
+        None => Ok(None),
+    }
+}
+
+fn is_empty(db: &dyn WalletStore) -> Result<bool, ToolkitError> {
+    Ok(db.scan("")?.is_empty())
+}
+
+/// Whether some wallet record, live or trashed, still holds its secret in clear. Only
+/// then does `migrate` need a passphrase.
+pub fn has_plaintext_secrets(db: &dyn WalletStore) -> Result<bool, ToolkitError> {
+    for prefix in MIGRATED_PREFIXES {
+        if db
+            .scan(prefix)?
+            .iter()
+            .any(|(_, value)| is_plaintext_record(value))
+        {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Upgrades every record to `SCHEMA_VERSION` in a single batch, together with the
+/// version marker, so a failed run leaves the database untouched. Without a passphrase,
+/// as when a database is opened, plaintext secrets are left for an explicit `db migrate`
+/// and their records fail.
+pub fn migrate(
+    db: &dyn WalletStore,
+    passphrase: Option<&str>,
+    dry_run: bool,
+) -> Result<MigrationReport, ToolkitError> {
+    let from = match read_schema_version(db)? {
+        Some(version) => version,
+        // A fresh database starts out at the current layout
+        None if is_empty(db)? => SCHEMA_VERSION,
+        None => 0,
+    };
+
+    if from > SCHEMA_VERSION {
+        return Err(ToolkitError::InvalidInput(format!(
+            "Database schema version {} is newer than this toolkit supports ({})",
+            from, SCHEMA_VERSION
+        )));
+    }
+
+    // Secrets encrypted now must open with the passphrase of the others
+    if let Some(passphrase) = passphrase.filter(|_| !dry_run) {
+        verify_passphrase(db, passphrase)?;
+    }
+
+    let pending: Vec<_> = MIGRATIONS.iter().filter(|m| m.version > from).collect();
+    let mut report = MigrationReport {
+        from,
+        to: SCHEMA_VERSION,
+        applied: pending.iter().map(|m| m.description).collect(),
+        ..MigrationReport::default()
+    };
+
+    let mut batch = StoreBatch::default();
+
+    if !pending.is_empty() {
+        for prefix in MIGRATED_PREFIXES {
+            for (key, value) in db.scan(prefix)? {
+                let mut upgraded: Option<Vec<u8>> = None;
+
+                for migration in &pending {
+                    let current = upgraded.as_deref().unwrap_or(&value);
+                    match (migration.upgrade)(current, passphrase) {
+                        Ok(Some(next)) => upgraded = Some(next),
+                        Ok(None) => {}
+                        Err(e) => {
+                            report.failed.push((key.clone(), e.to_string()));
+                            upgraded = None;
+                            break;
+                        }
+                    }
+                }
+
+                if let Some(upgraded) = upgraded {
+                    batch.put(&key, upgraded);
+                    report.upgraded.push(key);
+                }
+            }
+        }
+    }
+
+    if dry_run || !report.failed.is_empty() {
+        return Ok(report);
+    }
+
+    if read_schema_version(db)? != Some(SCHEMA_VERSION) {
+        batch.put(SCHEMA_VERSION_KEY, SCHEMA_VERSION.to_string());
+        db.write(batch)?;
+    }
+
+    Ok(report)
+}
+
+/// Reads every entry and reports the ones that are corrupt, outdated or unknown.
+/// With a passphrase, secrets are also decrypted and checked against their pubkeys.
+pub fn check_db(
+    db: &dyn WalletStore,
+    passphrase: Option<&str>,
+) -> Result<Vec<Issue>, ToolkitError> {
+    let mut issues = Vec::new();
+    let mut has_records = false;
+    let mut has_version = false;
+
+    for (key, value) in db.scan("")? {
+        let record_key = key.strip_prefix("trash:").unwrap_or(&key);
+        let result: Result<(), ToolkitError> = if key == SCHEMA_VERSION_KEY {
+            has_version = true;
+            match String::from_utf8_lossy(&value).parse::<u32>() {
+                Ok(SCHEMA_VERSION) => Ok(()),
+                Ok(version) if version < SCHEMA_VERSION => Err(ToolkitError::Decode(format!(
+                    "schema version {} is outdated, run `db migrate`",
+                    version
+                ))),
+                Ok(version) => Err(ToolkitError::Decode(format!(
+                    "schema version {} was written by a newer toolkit",
+                    version
+                ))),
+                Err(e) => Err(ToolkitError::Decode(format!(
+                    "invalid schema version: {}",
+                    e
+                ))),
+            }
+        } else if record_key.starts_with("wallet:") {
+            has_records = true;
+            validate_wallet_record(&value, passphrase)
+        } else if record_key.starts_with("seed:") {
+            has_records = true;
+            validate_seed_record(&value, passphrase)
+        } else if record_key.starts_with("watch:") {
+            validate_watch_record(&value)
+        } else if key.starts_with("contact:") {
+            validate_contact_record(&value)
+        } else {
+            Err(ToolkitError::Decode("unknown key".to_string()))
+        };
+
+        if let Err(e) = result {
+            issues.push(Issue {
+                key,
+                problem: e.to_string(),
+            });
+        }
+    }
+
+    if has_records && !has_version {
+        issues.push(Issue {
+            key: SCHEMA_VERSION_KEY.to_string(),
+            problem: "schema version is missing, run `db migrate`".to_string(),
+        });
+    }
+
+    Ok(issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::store::MemoryStore;
+    use crate::utils::wallet::{generate_keypair, get_wallet_key, read_wallet, Wallet};
+    use solana_sdk::{signature::Keypair, signer::Signer};
+
+    const PASSPHRASE: &str = "test passphrase";
+
+    fn plaintext_wallet() -> Wallet {
+        let keypair = Keypair::new();
+
+        Wallet {
+            pubkey: keypair.pubkey().to_string(),
+            secret_key: keypair.to_bytes().to_vec().into(),
+        }
+    }
+
+    #[test]
+    fn test_fresh_db_needs_no_migration() {
+        let db = MemoryStore::default();
+
+        let report = migrate(&db, None, false).unwrap();
+        assert_eq!(report.from, SCHEMA_VERSION);
+        assert!(report.applied.is_empty());
+        assert_eq!(read_schema_version(&db).unwrap(), Some(SCHEMA_VERSION));
+        assert!(check_db(&db, Some(PASSPHRASE)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_migrate_plaintext_wallets() {
+        let db = MemoryStore::default();
+
+        let wallet = plaintext_wallet();
+        db.put(
+            &get_wallet_key("legacy"),
+            &serde_json::to_vec(&wallet).unwrap(),
+        )
+        .unwrap();
+        generate_keypair(&db, "current", PASSPHRASE, false).unwrap();
+
+        let issues = check_db(&db, None).unwrap();
+        assert_eq!(issues.len(), 2);
+        assert!(has_plaintext_secrets(&db).unwrap());
+        assert!(migrate(&db, Some("other passphrase"), false).is_err());
+
+        // Secrets are only encrypted with a passphrase given for it
+        let report = migrate(&db, None, false).unwrap();
+        assert_eq!(report.failed.len(), 1);
+        assert!(report.failed[0].1.contains("run `db migrate`"));
+        assert_eq!(read_schema_version(&db).unwrap(), None);
+
+        // A dry run reports without writing anything
+        let report = migrate(&db, Some(PASSPHRASE), true).unwrap();
+        assert_eq!(report.from, 0);
+        assert_eq!(report.upgraded, vec!["wallet:legacy"]);
+        assert_eq!(read_schema_version(&db).unwrap(), None);
+
+        let report = migrate(&db, Some(PASSPHRASE), false).unwrap();
+        assert!(report.failed.is_empty());
+        assert_eq!(read_schema_version(&db).unwrap(), Some(SCHEMA_VERSION));
+        assert_eq!(read_wallet(&db, "legacy", PASSPHRASE).unwrap(), wallet);
+        assert!(check_db(&db, Some(PASSPHRASE)).unwrap().is_empty());
+
+        // Running again is a no-op
+        assert!(!has_plaintext_secrets(&db).unwrap());
+        let report = migrate(&db, None, false).unwrap();
+        assert!(report.upgraded.is_empty());
+    }
+
+    #[test]
+    fn test_migration_failure_writes_nothing() {
+        let db = MemoryStore::default();
+
+        db.put(
+            &get_wallet_key("legacy"),
+            &serde_json::to_vec(&plaintext_wallet()).unwrap(),
+        )
+        .unwrap();
+        db.put(&get_wallet_key("broken"), b"not json").unwrap();
+
+        let report = migrate(&db, Some(PASSPHRASE), false).unwrap();
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, "wallet:broken");
+        assert_eq!(read_schema_version(&db).unwrap(), None);
+    }
+
+    #[test]
+    fn test_newer_schema_is_rejected() {
+        let db = MemoryStore::default();
+
+        db.put(
+            SCHEMA_VERSION_KEY,
+            (SCHEMA_VERSION + 1).to_string().as_bytes(),
+        )
+        .unwrap();
+        assert!(migrate(&db, None, false).is_err());
+    }
+
+    #[test]
+    fn test_check_reports_corrupt_entries() {
+        let db = MemoryStore::default();
+
+        generate_keypair(&db, "good", PASSPHRASE, false).unwrap();
+        migrate(&db, None, false).unwrap();
+        db.put(&get_wallet_key("broken"), b"{\"pubkey\": 1}")
+            .unwrap();
+        db.put("seed:broken", b"[]").unwrap();
+        db.put("stray", b"?").unwrap();
+
+        let keys: Vec<_> = check_db(&db, None)
+            .unwrap()
+            .into_iter()
+            .map(|issue| issue.key)
+            .collect();
+        assert_eq!(keys, vec!["seed:broken", "stray", "wallet:broken"]);
+
+        // Wrong passphrase shows up once secrets are decrypted
+        let issues = check_db(&db, Some("wrong passphrase")).unwrap();
+        assert!(issues.iter().any(|issue| issue.key == "wallet:good"));
+    }
+}
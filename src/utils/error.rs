@@ -0,0 +1,185 @@
+use std::{fmt, io, string::FromUtf8Error};
+
+use solana_client::client_error::ClientError;
+use solana_sdk::pubkey::ParsePubkeyError;
+
+/// Everything that can go wrong in the toolkit, by kind. Each kind maps to its own
+/// process exit code so scripts can branch on why a command failed.
+#[derive(Debug)]
+pub enum ToolkitError {
+    /// A wallet, contact or record that does not exist
+    NotFound(String),
+    /// A wallet or contact name that is already taken
+    AlreadyExists(String),
+    /// Arguments that make no sense, like a 15 word seed phrase
+    InvalidInput(String),
+    InvalidPubkey(String),
+    /// Records, files or keys that cannot be decoded
+    Decode(String),
+    /// Wrong passphrase or a tampered secret
+    Crypto(String),
+    /// The wallet store failed to read or write
+    Storage(String),
+    Io(String),
+    /// The cluster could not be reached or rejected a request
+    Rpc(String),
+    InsufficientFunds {
+        needed: u64,
+        available: u64,
+    },
+    /// The faucet kept refusing airdrops after every retry
+    RateLimited(String),
+    /// The user declined a confirmation prompt
+    Cancelled(String),
+}
+
+impl ToolkitError {
+    /// Process exit code for this kind of error. These are stable: scripts rely on them.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ToolkitError::NotFound(_) => 10,
+            ToolkitError::AlreadyExists(_) => 11,
+            ToolkitError::InvalidInput(_) => 12,
+            ToolkitError::InvalidPubkey(_) => 13,
+            ToolkitError::Decode(_) => 14,
+            ToolkitError::Crypto(_) => 15,
+            ToolkitError::Storage(_) => 16,
+            ToolkitError::Io(_) => 17,
+            ToolkitError::Rpc(_) => 20,
+            ToolkitError::InsufficientFunds { .. } => 21,
+            ToolkitError::RateLimited(_) => 22,
+            ToolkitError::Cancelled(_) => 30,
+        }
+    }
+
+    /// Short name of this kind of error, as reported in JSON output.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ToolkitError::NotFound(_) => "not_found",
+            ToolkitError::AlreadyExists(_) => "already_exists",
+            ToolkitError::InvalidInput(_) => "invalid_input",
+            ToolkitError::InvalidPubkey(_) => "invalid_pubkey",
+            ToolkitError::Decode(_) => "decode",
+            ToolkitError::Crypto(_) => "crypto",
+            ToolkitError::Storage(_) => "storage",
+            ToolkitError::Io(_) => "io",
+            ToolkitError::Rpc(_) => "rpc",
+            ToolkitError::InsufficientFunds { .. } => "insufficient_funds",
+            ToolkitError::RateLimited(_) => "rate_limited",
+            ToolkitError::Cancelled(_) => "cancelled",
+        }
+    }
+}
+
+impl fmt::Display for ToolkitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToolkitError::NotFound(message)
+            | ToolkitError::AlreadyExists(message)
+            | ToolkitError::InvalidInput(message)
+            | ToolkitError::InvalidPubkey(message)
+            | ToolkitError::Decode(message)
+            | ToolkitError::Crypto(message)
+            | ToolkitError::Storage(message)
+            | ToolkitError::Io(message)
+            | ToolkitError::Rpc(message)
+            | ToolkitError::RateLimited(message)
+            | ToolkitError::Cancelled(message) => write!(f, "{}", message),
+            ToolkitError::InsufficientFunds { needed, available } => write!(
+                f,
+                "Insufficient funds: {} lamports needed, {} available",
+                needed, available
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ToolkitError {}
+
+impl From<serde_json::Error> for ToolkitError {
+    fn from(e: serde_json::Error) -> Self {
+        ToolkitError::Decode(format!("Could not decode JSON: {}", e))
+    }
+}
+
+impl From<FromUtf8Error> for ToolkitError {
+    fn from(e: FromUtf8Error) -> Self {
+        ToolkitError::Decode(format!("Invalid UTF-8: {}", e))
+    }
+}
+
+impl From<bs58::decode::Error> for ToolkitError {
+    fn from(e: bs58::decode::Error) -> Self {
+        ToolkitError::Decode(format!("Invalid base58: {}", e))
+    }
+}
+
+impl From<bip39::Error> for ToolkitError {
+    fn from(e: bip39::Error) -> Self {
+        ToolkitError::InvalidInput(format!("Invalid seed phrase: {}", e))
+    }
+}
+
+impl From<io::Error> for ToolkitError {
+    fn from(e: io::Error) -> Self {
+        ToolkitError::Io(e.to_string())
+    }
+}
+
+impl From<ParsePubkeyError> for ToolkitError {
+    fn from(e: ParsePubkeyError) -> Self {
+        ToolkitError::InvalidPubkey(format!("Invalid pubkey: {}", e))
+    }
+}
+
+impl From<ClientError> for ToolkitError {
+    fn from(e: ClientError) -> Self {
+        ToolkitError::Rpc(format!("RPC request failed: {}", e))
+    }
+}
+
+#[cfg(feature = "rocksdb")]
+impl From<rocksdb::Error> for ToolkitError {
+    fn from(e: rocksdb::Error) -> Self {
+        ToolkitError::Storage(format!("RocksDB error: {}", e))
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for ToolkitError {
+    fn from(e: rusqlite::Error) -> Self {
+        ToolkitError::Storage(format!("SQLite error: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_exit_codes_are_distinct() {
+        let errors = [
+            ToolkitError::NotFound(String::new()),
+            ToolkitError::AlreadyExists(String::new()),
+            ToolkitError::InvalidInput(String::new()),
+            ToolkitError::InvalidPubkey(String::new()),
+            ToolkitError::Decode(String::new()),
+            ToolkitError::Crypto(String::new()),
+            ToolkitError::Storage(String::new()),
+            ToolkitError::Io(String::new()),
+            ToolkitError::Rpc(String::new()),
+            ToolkitError::InsufficientFunds {
+                needed: 2,
+                available: 1,
+            },
+            ToolkitError::RateLimited(String::new()),
+            ToolkitError::Cancelled(String::new()),
+        ];
+
+        let codes: HashSet<_> = errors.iter().map(ToolkitError::exit_code).collect();
+        assert_eq!(codes.len(), errors.len());
+        // 1 is left for generic failures and 2 is a clap usage error
+        assert!(codes.iter().all(|&code| code > 2));
+    }
+}
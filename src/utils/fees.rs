@@ -0,0 +1,126 @@
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction, instruction::Instruction, pubkey::Pubkey,
+};
+
+use super::error::ToolkitError;
+use super::output::verbose;
+
+/// `getRecentPrioritizationFees` looks at no more than this many accounts.
+const MAX_FEE_ACCOUNTS: usize = 128;
+
+/// Price of a compute unit, in micro-lamports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriorityFee {
+    Fixed(u64),
+    /// A percentile of what recent transactions writing to the same accounts paid
+    Auto {
+        percentile: u8,
+    },
+}
+
+/// ComputeBudget settings put in front of every transaction. Empty settings add nothing, and
+/// the runtime defaults apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ComputeBudget {
+    pub priority_fee: Option<PriorityFee>,
+    pub unit_limit: Option<u32>,
+}
+
+impl ComputeBudget {
+    /// The ComputeBudget instructions for a transaction made of `instructions` and paid by
+    /// `payer`. The fee of a message holding both includes the priority fee, since it is
+    /// the price times the requested unit limit.
+    pub fn instructions(
+        &self,
+        client: &RpcClient,
+        payer: &Pubkey,
+        instructions: &[Instruction],
+    ) -> Result<Vec<Instruction>, ToolkitError> {
+        let mut budget = Vec::new();
+
+        if let Some(unit_limit) = self.unit_limit {
+            budget.push(ComputeBudgetInstruction::set_compute_unit_limit(unit_limit));
+        }
+
+        let price = match self.priority_fee {
+            None => return Ok(budget),
+            Some(PriorityFee::Fixed(price)) => price,
+            Some(PriorityFee::Auto { percentile }) => {
+                let accounts = writable_accounts(payer, instructions);
+                let fees: Vec<u64> = client
+                    .get_recent_prioritization_fees(&accounts)?
+                    .iter()
+                    .map(|fee| fee.prioritization_fee)
+                    .collect();
+                let price = fee_percentile(fees, percentile);
+                verbose!(
+                    "Priority fee: {} micro-lamports per compute unit, percentile {}",
+                    price,
+                    percentile
+                );
+                price
+            }
+        };
+        if price > 0 {
+            budget.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+        }
+
+        Ok(budget)
+    }
+}
+
+/// Accounts a transaction locks for writing, which is what its priority competes for.
+fn writable_accounts(payer: &Pubkey, instructions: &[Instruction]) -> Vec<Pubkey> {
+    let mut accounts = vec![*payer];
+
+    for meta in instructions.iter().flat_map(|ix| &ix.accounts) {
+        if meta.is_writable && !accounts.contains(&meta.pubkey) {
+            accounts.push(meta.pubkey);
+        }
+    }
+    accounts.truncate(MAX_FEE_ACCOUNTS);
+
+    accounts
+}
+
+/// Nearest-rank percentile of `fees`, 0 when there are none.
+fn fee_percentile(mut fees: Vec<u64>, percentile: u8) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+    fees.sort_unstable();
+
+    let rank = (usize::from(percentile.min(100)) * fees.len()).div_ceil(100);
+    fees[rank.saturating_sub(1)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::system_instruction::transfer;
+
+    #[test]
+    fn test_fee_percentile() {
+        assert_eq!(fee_percentile(vec![], 75), 0);
+        assert_eq!(fee_percentile(vec![7], 50), 7);
+
+        let fees = vec![0, 40, 10, 30, 20, 0, 0, 50, 1000, 0];
+        assert_eq!(fee_percentile(fees.clone(), 0), 0);
+        assert_eq!(fee_percentile(fees.clone(), 50), 10);
+        assert_eq!(fee_percentile(fees.clone(), 75), 40);
+        assert_eq!(fee_percentile(fees.clone(), 90), 50);
+        assert_eq!(fee_percentile(fees, 100), 1000);
+    }
+
+    #[test]
+    fn test_writable_accounts() {
+        let payer = Pubkey::new_unique();
+        let from = Pubkey::new_unique();
+        let to = Pubkey::new_unique();
+
+        let accounts =
+            writable_accounts(&payer, &[transfer(&from, &to, 1), transfer(&payer, &to, 1)]);
+        assert_eq!(accounts, vec![payer, from, to]);
+    }
+}
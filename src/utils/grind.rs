@@ -0,0 +1,215 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+use super::error::ToolkitError;
+
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// What a ground pubkey has to look like.
+#[derive(Debug, Clone)]
+pub struct GrindTarget {
+    prefix: String,
+    suffix: String,
+    ignore_case: bool,
+}
+
+impl GrindTarget {
+    pub fn new(prefix: &str, suffix: &str, ignore_case: bool) -> Result<GrindTarget, ToolkitError> {
+        if prefix.is_empty() && suffix.is_empty() {
+            return Err(ToolkitError::InvalidInput(
+                "Provide a prefix, a suffix or both to grind for".to_string(),
+            ));
+        }
+
+        // Reject characters that can never show up in a base58 pubkey
+        for c in prefix.chars().chain(suffix.chars()) {
+            if matching_chars(c, ignore_case) == 0 {
+                return Err(ToolkitError::InvalidInput(format!(
+                    "'{}' is not a valid base58 character",
+                    c
+                )));
+            }
+        }
+
+        let normalize = |s: &str| {
+            if ignore_case {
+                s.to_lowercase()
+            } else {
+                s.to_string()
+            }
+        };
+
+        Ok(GrindTarget {
+            prefix: normalize(prefix),
+            suffix: normalize(suffix),
+            ignore_case,
+        })
+    }
+
+    pub fn matches(&self, pubkey: &str) -> bool {
+        if self.ignore_case {
+            let pubkey = pubkey.to_lowercase();
+            pubkey.starts_with(&self.prefix) && pubkey.ends_with(&self.suffix)
+        } else {
+            pubkey.starts_with(&self.prefix) && pubkey.ends_with(&self.suffix)
+        }
+    }
+
+    /// Average number of keypairs to try before a match, treating every base58
+    /// character of the pubkey as uniformly distributed.
+    pub fn expected_attempts(&self) -> f64 {
+        self.prefix
+            .chars()
+            .chain(self.suffix.chars())
+            .map(|c| 58.0 / matching_chars(c, self.ignore_case) as f64)
+            .product()
+    }
+}
+
+/// Number of base58 characters that would match `c`.
+fn matching_chars(c: char, ignore_case: bool) -> usize {
+    BASE58_ALPHABET
+        .chars()
+        .filter(|a| {
+            if ignore_case {
+                a.eq_ignore_ascii_case(&c)
+            } else {
+                *a == c
+            }
+        })
+        .count()
+}
+
+fn format_duration(seconds: f64) -> String {
+    if !seconds.is_finite() {
+        return "unknown".to_string();
+    }
+
+    match seconds as u64 {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3_600 => format!("{}m {}s", s / 60, s % 60),
+        s if s < 86_400 => format!("{}h {}m", s / 3_600, (s % 3_600) / 60),
+        s => format!("{}d {}h", s / 86_400, (s % 86_400) / 3_600),
+    }
+}
+
+/// Searches keypairs on `threads` worker threads until one matches `target`,
+/// reporting attempts per second and an estimated time to a match every second.
+/// Progress goes to stderr so the command's output stays clean.
+pub fn grind_keypair(target: &GrindTarget, threads: usize) -> Keypair {
+    let found = Arc::new(AtomicBool::new(false));
+    let attempts = Arc::new(AtomicU64::new(0));
+    let (sender, receiver) = mpsc::channel();
+
+    let workers: Vec<_> = (0..threads.max(1))
+        .map(|_| {
+            let target = target.clone();
+            let found = Arc::clone(&found);
+            let attempts = Arc::clone(&attempts);
+            let sender = sender.clone();
+
+            thread::spawn(move || {
+                while !found.load(Ordering::Relaxed) {
+                    let keypair = Keypair::new();
+                    attempts.fetch_add(1, Ordering::Relaxed);
+
+                    if target.matches(&keypair.pubkey().to_string()) {
+                        found.store(true, Ordering::Relaxed);
+                        let _ = sender.send(keypair);
+                    }
+                }
+            })
+        })
+        .collect();
+
+    let expected = target.expected_attempts();
+    let started = Instant::now();
+    eprintln!(
+        "Grinding on {} threads, expecting ~{:.0} attempts",
+        workers.len(),
+        expected
+    );
+
+    let keypair = loop {
+        match receiver.recv_timeout(Duration::from_secs(1)) {
+            Ok(keypair) => break keypair,
+            Err(_) => {
+                let tried = attempts.load(Ordering::Relaxed);
+                let rate = tried as f64 / started.elapsed().as_secs_f64();
+
+                // Every attempt is independent, so the expected wait never shrinks
+                eprint!(
+                    "\r{} attempts, {:.0} attempts/s, estimated time to match: {}   ",
+                    tried,
+                    rate,
+                    format_duration(expected / rate)
+                );
+            }
+        }
+    };
+
+    found.store(true, Ordering::Relaxed);
+    for worker in workers {
+        // The match is already in hand, a worker that died does not change it
+        let _ = worker.join();
+    }
+
+    eprintln!(
+        "\nFound {} after {} attempts in {}",
+        keypair.pubkey(),
+        attempts.load(Ordering::Relaxed),
+        format_duration(started.elapsed().as_secs_f64())
+    );
+
+    keypair
+}
+
+/// Number of worker threads to grind with by default: one per CPU core.
+pub fn default_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grind_target_validation() {
+        assert!(GrindTarget::new("", "", false).is_err());
+        assert!(GrindTarget::new("0", "", false).is_err());
+        assert!(GrindTarget::new("", "Il", false).is_err());
+
+        // 'o' and 'i' exist in one case only, so they stay valid when ignoring case
+        assert!(GrindTarget::new("O", "I", true).is_ok());
+    }
+
+    #[test]
+    fn test_grind_target_matches() {
+        let exact = GrindTarget::new("Ab", "z", false).unwrap();
+        assert!(exact.matches("Ab123z"));
+        assert!(!exact.matches("ab123z"));
+
+        let loose = GrindTarget::new("Ab", "Z", true).unwrap();
+        assert!(loose.matches("aB123z"));
+        assert!(!loose.matches("aC123z"));
+
+        assert_eq!(exact.expected_attempts(), 58.0 * 58.0 * 58.0);
+        assert_eq!(loose.expected_attempts(), 29.0 * 29.0 * 29.0);
+    }
+
+    #[test]
+    fn test_grind_keypair_finds_match() {
+        let target = GrindTarget::new("a", "", false).unwrap();
+        let keypair = grind_keypair(&target, 2);
+
+        assert!(keypair.pubkey().to_string().starts_with('a'));
+    }
+}
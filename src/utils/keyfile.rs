@@ -0,0 +1,186 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+};
+
+use serde::Deserialize;
+use solana_sdk::{signature::Keypair, signer::Signer};
+use zeroize::Zeroizing;
+
+use super::crypto::SecretBytes;
+use super::error::ToolkitError;
+use super::meta::Origin;
+use super::store::WalletStore;
+use super::wallet::{save_wallet_to_db, Wallet};
+
+/// The keypair file layouts we can read: the `[u8; 64]` array written by
+/// `solana-keygen` and `anchor`, and the old `{ pubkey, secret_key }` wallet
+/// files like `dev-wallet.json` and `wba-wallet.json`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeypairFile {
+    Bytes(SecretBytes),
+    Wallet(Wallet),
+}
+
+pub fn read_wallet_from_file(path: &Path) -> Result<Wallet, ToolkitError> {
+    let contents = Zeroizing::new(fs::read_to_string(path)?);
+
+    let secret_key = match serde_json::from_str::<KeypairFile>(&contents)? {
+        KeypairFile::Bytes(bytes) => bytes,
+        KeypairFile::Wallet(wallet) => {
+            if wallet.keypair()?.pubkey().to_string() != wallet.pubkey {
+                return Err(ToolkitError::Decode(
+                    "Public key does not match the secret key in wallet file".to_string(),
+                ));
+            }
+            wallet.secret_key
+        }
+    };
+
+    // Make sure the bytes are a valid ed25519 keypair
+    let keypair = Keypair::from_bytes(&secret_key)
+        .map_err(|e| ToolkitError::Decode(format!("Invalid keypair file: {}", e)))?;
+
+    Ok(Wallet {
+        pubkey: keypair.pubkey().to_string(),
+        secret_key,
+    })
+}
+
+/// Writes the wallet as a `solana-keygen` compatible JSON array, readable only by its owner.
+/// Existing files are never overwritten.
+pub fn write_wallet_to_file(wallet: &Wallet, path: &Path) -> Result<(), ToolkitError> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options
+        .open(path)
+        .map_err(|e| ToolkitError::Io(format!("Failed to create {}: {}", path.display(), e)))?;
+    file.write_all(serde_json::to_string(&wallet.secret_key)?.as_bytes())?;
+
+    Ok(())
+}
+
+pub fn import_wallet_file(
+    db: &dyn WalletStore,
+    path: &Path,
+    name: &str,
+    passphrase: &str,
+    force: bool,
+) -> Result<Wallet, ToolkitError> {
+    let wallet = read_wallet_from_file(path)?;
+    save_wallet_to_db(&wallet, db, name, passphrase, force, Origin::FileImport)?;
+
+    Ok(wallet)
+}
+
+/// The outcome of importing one keypair file, keyed by wallet name.
+pub type FileImport = (String, Result<Wallet, ToolkitError>);
+
+/// Imports every `*.json` keypair in `dir`, naming each wallet after its file stem.
+/// Returns the outcome per file so one bad file does not stop the others.
+pub fn import_wallet_dir(
+    db: &dyn WalletStore,
+    dir: &Path,
+    passphrase: &str,
+    force: bool,
+) -> Result<Vec<FileImport>, ToolkitError> {
+    let mut paths = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "json"))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    let results = paths
+        .iter()
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_str()?.to_string();
+            let result = import_wallet_file(db, path, &name, passphrase, force);
+            Some((name, result))
+        })
+        .collect();
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::store::MemoryStore;
+    use tempdir::TempDir;
+
+    const PASSPHRASE: &str = "test passphrase";
+
+    fn test_wallet() -> Wallet {
+        let keypair = Keypair::new();
+
+        Wallet {
+            pubkey: keypair.pubkey().to_string(),
+            secret_key: keypair.to_bytes().to_vec().into(),
+        }
+    }
+
+    #[test]
+    fn test_export_and_import_file() {
+        let tmp_dir = TempDir::new("keyfile").unwrap();
+        let path = tmp_dir.path().join("id.json");
+        let wallet = test_wallet();
+
+        write_wallet_to_file(&wallet, &path).unwrap();
+        assert_eq!(read_wallet_from_file(&path).unwrap(), wallet);
+
+        // Never clobber an existing key file
+        assert!(write_wallet_to_file(&wallet, &path).is_err());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_import_legacy_wallet_file() {
+        let tmp_dir = TempDir::new("keyfile").unwrap();
+        let path = tmp_dir.path().join("dev-wallet.json");
+        let wallet = test_wallet();
+
+        fs::write(&path, serde_json::to_string_pretty(&wallet).unwrap()).unwrap();
+        assert_eq!(read_wallet_from_file(&path).unwrap(), wallet);
+
+        let mismatched = Wallet {
+            pubkey: test_wallet().pubkey,
+            secret_key: wallet.secret_key,
+        };
+        fs::write(&path, serde_json::to_string(&mismatched).unwrap()).unwrap();
+        assert!(read_wallet_from_file(&path).is_err());
+    }
+
+    #[test]
+    fn test_import_wallet_dir() {
+        let tmp_dir = TempDir::new("keyfile").unwrap();
+        let db = MemoryStore::default();
+
+        write_wallet_to_file(&test_wallet(), &tmp_dir.path().join("alice.json")).unwrap();
+        write_wallet_to_file(&test_wallet(), &tmp_dir.path().join("bob.json")).unwrap();
+        fs::write(tmp_dir.path().join("broken.json"), "[1, 2, 3]").unwrap();
+        fs::write(tmp_dir.path().join("notes.txt"), "not a key").unwrap();
+
+        let results = import_wallet_dir(&db, tmp_dir.path(), PASSPHRASE, false).unwrap();
+        let names: Vec<_> = results.iter().map(|(name, _)| name.as_str()).collect();
+
+        assert_eq!(names, vec!["alice", "bob", "broken"]);
+        assert!(results[0].1.is_ok());
+        assert!(results[1].1.is_ok());
+        assert!(results[2].1.is_err());
+    }
+}
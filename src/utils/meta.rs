@@ -0,0 +1,187 @@
+use std::{
+    collections::BTreeSet,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use super::cluster::Cluster;
+
+/// How a wallet came into the store.
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Default,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum Origin {
+    /// Fresh random keypair, including vanity grinds
+    #[default]
+    Generated,
+    /// Imported from a base58 secret key
+    Base58Import,
+    /// Imported from a JSON keypair file
+    FileImport,
+    /// Derived from a BIP39 seed phrase
+    Derived,
+    /// Public key only, there is no secret to sign with
+    WatchOnly,
+}
+
+/// Non-secret information kept next to every wallet.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct WalletMeta {
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+    /// Unix timestamp in seconds, 0 when unknown
+    #[serde(default)]
+    pub created_at: u64,
+    #[serde(default)]
+    pub origin: Origin,
+    /// Cluster moniker or URL used for this wallet when `--cluster-url` is not given
+    #[serde(default)]
+    pub cluster: Option<String>,
+    /// Unix timestamp in seconds of the last `clean-wallet` that emptied it
+    #[serde(default)]
+    pub swept_at: Option<u64>,
+}
+
+impl WalletMeta {
+    pub fn new(origin: Origin) -> WalletMeta {
+        WalletMeta {
+            created_at: now(),
+            origin,
+            ..WalletMeta::default()
+        }
+    }
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Formats a unix timestamp as `YYYY-MM-DD HH:MM:SS UTC`.
+pub fn format_timestamp(timestamp: u64) -> String {
+    if timestamp == 0 {
+        return "unknown".to_string();
+    }
+
+    let days = (timestamp / 86_400) as i64;
+    let seconds = timestamp % 86_400;
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        seconds / 3_600,
+        (seconds % 3_600) / 60,
+        seconds % 60
+    )
+}
+
+/// What `list-wallets` sorts on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Default)]
+pub enum SortKey {
+    #[default]
+    Name,
+    Created,
+    Origin,
+}
+
+/// Criteria a wallet has to meet to be listed. Empty criteria match everything.
+#[derive(Debug, Clone, Default)]
+pub struct WalletFilter {
+    pub tags: Vec<String>,
+    pub origin: Option<Origin>,
+    pub cluster: Option<Cluster>,
+    /// Case-insensitive substring of the name or description
+    pub search: Option<String>,
+}
+
+impl WalletFilter {
+    pub fn matches(&self, name: &str, meta: &WalletMeta) -> bool {
+        let search_matches = self.search.as_ref().is_none_or(|search| {
+            let search = search.to_lowercase();
+            name.to_lowercase().contains(&search)
+                || meta
+                    .description
+                    .as_ref()
+                    .is_some_and(|description| description.to_lowercase().contains(&search))
+        });
+
+        self.tags.iter().all(|tag| meta.tags.contains(tag))
+            && self.origin.is_none_or(|origin| origin == meta.origin)
+            && self.cluster.as_ref().is_none_or(|cluster| {
+                meta.cluster
+                    .as_deref()
+                    .and_then(|c| c.parse::<Cluster>().ok())
+                    .as_ref()
+                    == Some(cluster)
+            })
+            && search_matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "unknown");
+        assert_eq!(format_timestamp(86_400), "1970-01-02 00:00:00 UTC");
+        assert_eq!(format_timestamp(1_722_470_400), "2024-08-01 00:00:00 UTC");
+        assert_eq!(format_timestamp(951_827_696), "2000-02-29 12:34:56 UTC");
+    }
+
+    #[test]
+    fn test_wallet_filter() {
+        let mut meta = WalletMeta::new(Origin::FileImport);
+        meta.description = Some("Program upgrade authority".to_string());
+        meta.tags.insert("mainnet".to_string());
+
+        assert!(WalletFilter::default().matches("authority", &meta));
+
+        let by_tag = WalletFilter {
+            tags: vec!["mainnet".to_string()],
+            ..WalletFilter::default()
+        };
+        assert!(by_tag.matches("authority", &meta));
+
+        let by_origin = WalletFilter {
+            origin: Some(Origin::Generated),
+            ..WalletFilter::default()
+        };
+        assert!(!by_origin.matches("authority", &meta));
+
+        let by_search = WalletFilter {
+            search: Some("UPGRADE".to_string()),
+            ..WalletFilter::default()
+        };
+        assert!(by_search.matches("authority", &meta));
+        assert!(!by_search.matches("payer", &WalletMeta::default()));
+
+        meta.cluster = Some("https://api.devnet.solana.com".to_string());
+        let by_cluster = WalletFilter {
+            cluster: Some(Cluster::Devnet),
+            ..WalletFilter::default()
+        };
+        assert!(by_cluster.matches("authority", &meta));
+        assert!(!by_cluster.matches("payer", &WalletMeta::default()));
+    }
+}
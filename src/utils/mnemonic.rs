@@ -0,0 +1,130 @@
+use bip39::{Language, Mnemonic};
+use solana_sdk::{
+    derivation_path::DerivationPath, signature::Keypair,
+    signer::keypair::keypair_from_seed_and_derivation_path,
+};
+
+use super::error::ToolkitError;
+
+/// Generates a new English BIP39 mnemonic with 12 or 24 words.
+pub fn generate_mnemonic(word_count: usize) -> Result<Mnemonic, ToolkitError> {
+    if word_count != 12 && word_count != 24 {
+        return Err(ToolkitError::InvalidInput(format!(
+            "Mnemonic must have 12 or 24 words, got {}",
+            word_count
+        )));
+    }
+
+    Ok(Mnemonic::generate_in(Language::English, word_count)?)
+}
+
+/// Parses a seed phrase, tolerating extra whitespace and mixed case.
+pub fn parse_mnemonic(phrase: &str) -> Result<Mnemonic, ToolkitError> {
+    let normalized = phrase
+        .split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Ok(Mnemonic::parse_in_normalized(
+        Language::English,
+        &normalized,
+    )?)
+}
+
+/// The largest account index: the account is a hardened path level, below 2^31.
+pub const MAX_ACCOUNT_INDEX: u32 = (1 << 31) - 1;
+
+/// Refuses an account index outside the hardened range of the Solana BIP44 path.
+pub fn check_account_index(account: u32) -> Result<(), ToolkitError> {
+    if account > MAX_ACCOUNT_INDEX {
+        return Err(ToolkitError::InvalidInput(format!(
+            "Account index {} is out of range, it must be at most {}",
+            account, MAX_ACCOUNT_INDEX
+        )));
+    }
+
+    Ok(())
+}
+
+/// Derives the keypair for `account` along the Solana BIP44 path m/44'/501'/account'/0',
+/// the same path Phantom and `solana-keygen --derivation-path` use.
+pub fn keypair_from_mnemonic(
+    mnemonic: &Mnemonic,
+    seed_passphrase: &str,
+    account: u32,
+) -> Result<Keypair, ToolkitError> {
+    keypair_from_seed(&mnemonic.to_seed(seed_passphrase), account)
+}
+
+/// Derives the keypair for `account` from a raw 64-byte BIP39 seed.
+pub fn keypair_from_seed(seed: &[u8], account: u32) -> Result<Keypair, ToolkitError> {
+    check_account_index(account)?;
+    let derivation_path = DerivationPath::new_bip44(Some(account), Some(0));
+
+    keypair_from_seed_and_derivation_path(seed, Some(derivation_path))
+        .map_err(|e| ToolkitError::Crypto(format!("Failed to derive keypair: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signer::Signer;
+
+    #[test]
+    fn test_generate_mnemonic_word_counts() {
+        assert_eq!(generate_mnemonic(12).unwrap().word_count(), 12);
+        assert_eq!(generate_mnemonic(24).unwrap().word_count(), 24);
+        assert!(generate_mnemonic(15).is_err());
+    }
+
+    #[test]
+    fn test_keypair_from_mnemonic_is_deterministic() {
+        let mnemonic = generate_mnemonic(12).unwrap();
+        let phrase = mnemonic.to_string().to_uppercase();
+
+        let first = keypair_from_mnemonic(&mnemonic, "", 0).unwrap();
+        let recovered = keypair_from_mnemonic(&parse_mnemonic(&phrase).unwrap(), "", 0).unwrap();
+        assert_eq!(first.pubkey(), recovered.pubkey());
+
+        // Another account index or seed passphrase gives another key
+        let second = keypair_from_mnemonic(&mnemonic, "", 1).unwrap();
+        let protected = keypair_from_mnemonic(&mnemonic, "extra", 0).unwrap();
+        assert_ne!(first.pubkey(), second.pubkey());
+        assert_ne!(first.pubkey(), protected.pubkey());
+    }
+
+    #[test]
+    fn test_keypair_from_mnemonic_vectors() {
+        // The Solana Cookbook vector, which solana-keygen and Phantom restore to the same keys
+        let mnemonic = parse_mnemonic(
+            "neither lonely flavor argue grass remind eye tag avocado spot unusual intact",
+        )
+        .unwrap();
+        let pubkey = |seed_passphrase: &str, account: u32| {
+            keypair_from_mnemonic(&mnemonic, seed_passphrase, account)
+                .unwrap()
+                .pubkey()
+                .to_string()
+        };
+
+        assert_eq!(
+            pubkey("", 0),
+            "5vftMkHL72JaJG6ExQfGAsT2uGVHpRR7oTNUPMs68Y2N"
+        );
+        assert_eq!(
+            pubkey("", 1),
+            "GcXbfQ5yY3uxCyBNDPBbR5FjumHf89E7YHXuULfGDBBv"
+        );
+        // The seed passphrase goes into the BIP39 seed, before the m/44'/501'/0'/0' derivation
+        assert_eq!(
+            pubkey("wba turbin3", 0),
+            "7x3bo6c2RQCykZ31iPvd9yPWedvteu3hX127qix3woQQ"
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_mnemonic() {
+        assert!(parse_mnemonic("not a real seed phrase at all").is_err());
+    }
+}
@@ -1,2 +1,19 @@
+pub mod address;
+pub mod amount;
+pub mod cluster;
+pub mod config;
+pub mod crypto;
+pub mod db;
+pub mod error;
+pub mod fees;
+pub mod grind;
+pub mod keyfile;
+pub mod meta;
+pub mod mnemonic;
+pub mod output;
+pub mod prereq;
+pub mod seed;
 pub mod solana;
+pub mod store;
+pub mod token;
 pub mod wallet;
@@ -0,0 +1,345 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use super::error::ToolkitError;
+
+/// How a command prints what it did.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    /// Sentences for people
+    #[default]
+    Text,
+    /// One JSON document per command, for scripts and CI jobs
+    Json,
+    /// Aligned columns
+    Table,
+    /// Comma-separated records with a header, for spreadsheets
+    Csv,
+}
+
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+pub fn is_verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+/// Debug chatter: printed to stderr with `--verbose` only, stdout is reserved for the
+/// command's own output.
+macro_rules! verbose {
+    ($($arg:tt)*) => {
+        if $crate::utils::output::is_verbose() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+pub(crate) use verbose;
+
+/// What a command did: prose for people, and the same facts as a JSON document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    text: String,
+    data: Value,
+}
+
+impl Report {
+    pub fn new(text: impl Into<String>, data: Value) -> Report {
+        Report {
+            text: text.into(),
+            data,
+        }
+    }
+
+    pub fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Text => self.text.clone(),
+            OutputFormat::Json => self.data.to_string(),
+            OutputFormat::Table => render_table(&self.data),
+            OutputFormat::Csv => render_csv(&self.data),
+        }
+    }
+}
+
+/// How raw account data is printed.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum DataEncoding {
+    #[default]
+    Hex,
+    Base64,
+}
+
+impl DataEncoding {
+    /// The data as one string, for JSON documents.
+    pub fn encode(&self, data: &[u8]) -> String {
+        match self {
+            DataEncoding::Hex => data.iter().map(|byte| format!("{:02x}", byte)).collect(),
+            DataEncoding::Base64 => STANDARD.encode(data),
+        }
+    }
+
+    /// The data for people: hex as offset-prefixed rows of 16 bytes, base64 as is.
+    pub fn dump(&self, data: &[u8]) -> String {
+        match self {
+            DataEncoding::Hex => data
+                .chunks(16)
+                .enumerate()
+                .map(|(row, chunk)| format!("{:08x}  {}", row * 16, hex_row(chunk)))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            DataEncoding::Base64 => self.encode(data),
+        }
+    }
+}
+
+fn hex_row(chunk: &[u8]) -> String {
+    chunk
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The document printed instead of a report when a command fails in JSON mode.
+pub fn error_document(error: &ToolkitError) -> Value {
+    json!({
+        "error": {
+            "kind": error.kind(),
+            "code": error.exit_code(),
+            "message": error.to_string(),
+        }
+    })
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => "-".to_string(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) if items.iter().all(|item| !item.is_object()) => {
+            items.iter().map(cell).collect::<Vec<_>>().join(",")
+        }
+        other => other.to_string(),
+    }
+}
+
+fn is_row_list(value: &Value) -> bool {
+    matches!(value, Value::Array(items) if !items.is_empty() && items.iter().all(Value::is_object))
+}
+
+fn columns(rows: &[Value]) -> Vec<String> {
+    let mut columns: Vec<String> = Vec::new();
+    for key in rows.iter().filter_map(Value::as_object).flat_map(Map::keys) {
+        if !columns.contains(key) {
+            columns.push(key.clone());
+        }
+    }
+
+    columns
+}
+
+fn format_rows(header: &[String], rows: &[Vec<String>]) -> String {
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain([header[i].chars().count()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let line = |cells: &[String]| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    let separator: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+
+    let mut lines = vec![line(header), line(&separator)];
+    lines.extend(rows.iter().map(|row| line(row)));
+
+    lines.join("\n")
+}
+
+/// Lists of objects become one row per object; anything else is a field/value table,
+/// with nested lists of objects as their own tables underneath.
+fn render_table(data: &Value) -> String {
+    if is_row_list(data) {
+        let rows = data.as_array().map(Vec::as_slice).unwrap_or_default();
+        let header = columns(rows);
+        let cells: Vec<Vec<String>> = rows
+            .iter()
+            .map(|row| {
+                header
+                    .iter()
+                    .map(|column| cell(row.get(column).unwrap_or(&Value::Null)))
+                    .collect()
+            })
+            .collect();
+
+        return format_rows(&header, &cells);
+    }
+
+    let fields = match data {
+        Value::Object(fields) => fields,
+        other => return cell(other),
+    };
+
+    let mut sections = Vec::new();
+    let scalars: Vec<Vec<String>> = fields
+        .iter()
+        .filter(|(_, value)| !is_row_list(value))
+        .map(|(key, value)| vec![key.clone(), cell(value)])
+        .collect();
+    if !scalars.is_empty() {
+        sections.push(format_rows(
+            &["field".to_string(), "value".to_string()],
+            &scalars,
+        ));
+    }
+    for (key, value) in fields.iter().filter(|(_, value)| is_row_list(value)) {
+        sections.push(format!("{}:\n{}", key, render_table(value)));
+    }
+
+    sections.join("\n\n")
+}
+
+/// A list of objects is one record per object. A document holding such a list prints the
+/// first one only, so the output stays one valid CSV file; anything else is field,value records.
+fn render_csv(data: &Value) -> String {
+    let rows = match data {
+        Value::Object(fields) => match fields.values().find(|value| is_row_list(value)) {
+            Some(rows) => rows,
+            None => {
+                let mut lines = vec!["field,value".to_string()];
+                lines.extend(
+                    fields
+                        .iter()
+                        .map(|(key, value)| csv_record(&[key.clone(), cell(value)])),
+                );
+                return lines.join("\n");
+            }
+        },
+        other => other,
+    };
+    if !is_row_list(rows) {
+        return csv_record(&[cell(rows)]);
+    }
+
+    let rows = rows.as_array().map(Vec::as_slice).unwrap_or_default();
+    let header = columns(rows);
+    let mut lines = vec![csv_record(&header)];
+    lines.extend(rows.iter().map(|row| {
+        let cells: Vec<String> = header
+            .iter()
+            .map(|column| match row.get(column) {
+                None | Some(Value::Null) => String::new(),
+                Some(value) => cell(value),
+            })
+            .collect();
+        csv_record(&cells)
+    }));
+
+    lines.join("\n")
+}
+
+/// Cells joined with commas, quoted when they hold a comma, a quote or a line break.
+fn csv_record(cells: &[String]) -> String {
+    cells
+        .iter()
+        .map(|cell| {
+            if cell.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_formats() {
+        let report = Report::new(
+            "Wallet payer saved",
+            json!({ "name": "payer", "tags": ["dev", "ops"], "cluster": null }),
+        );
+
+        assert_eq!(report.render(OutputFormat::Text), "Wallet payer saved");
+
+        let document: Value = serde_json::from_str(&report.render(OutputFormat::Json)).unwrap();
+        assert_eq!(document["name"], "payer");
+
+        let table = report.render(OutputFormat::Table);
+        assert!(table.starts_with("field"));
+        assert!(table.contains("tags     dev,ops"));
+        assert!(table.contains("cluster  -"));
+    }
+
+    #[test]
+    fn test_data_encoding() {
+        let data: Vec<u8> = (0..20).collect();
+
+        assert_eq!(DataEncoding::Hex.encode(&data[..3]), "000102");
+        assert_eq!(DataEncoding::Base64.encode(b"wba"), "d2Jh");
+
+        let dump = DataEncoding::Hex.dump(&data);
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("00000000  00 01 02"));
+        assert_eq!(lines[1], "00000010  10 11 12 13");
+    }
+
+    #[test]
+    fn test_render_row_table() {
+        let report = Report::new(
+            "",
+            json!({ "wallets": [
+                { "name": "a", "pubkey": "Abc" },
+                { "name": "long-name", "pubkey": "Xyz" },
+            ]}),
+        );
+
+        let table = report.render(OutputFormat::Table);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "wallets:");
+        assert_eq!(lines[1], "name       pubkey");
+        assert_eq!(lines[3], "a          Abc");
+        assert_eq!(lines[4], "long-name  Xyz");
+    }
+
+    #[test]
+    fn test_render_csv() {
+        let report = Report::new(
+            "",
+            json!({ "count": 2, "wallets": [
+                { "name": "a", "tags": ["dev", "ops"] },
+                { "name": "say \"hi\"", "description": null },
+            ]}),
+        );
+        assert_eq!(
+            report.render(OutputFormat::Csv),
+            "name,tags,description\na,\"dev,ops\",\n\"say \"\"hi\"\"\",,"
+        );
+
+        let report = Report::new("", json!({ "name": "payer" }));
+        assert_eq!(report.render(OutputFormat::Csv), "field,value\nname,payer");
+    }
+}
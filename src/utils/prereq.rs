@@ -0,0 +1,719 @@
+use std::{collections::HashMap, fmt, fs, path::Path};
+
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{
+    account::Account, hash::hashv, pubkey, pubkey::Pubkey, signer::Signer, system_program,
+};
+
+use super::address::parse_pubkey;
+use super::error::ToolkitError;
+use super::solana::{self, Outcome};
+use super::wallet::Wallet;
+use crate::programs::wba_prereq::{CompleteArgs, UpdateArgs, WbaPrereqProgram};
+
+/// The prereq program, `metadata.address` in its IDL.
+pub const PREREQ_PROGRAM_ID: Pubkey = pubkey!("HC2oqz2p6DEWfrahenqdq2moUcga9c9biqRBcdK3XKU1");
+
+/// Seed of the `prereq` account, followed by the signer's pubkey.
+const PREREQ_SEED: &[u8] = b"prereq";
+
+/// Anchor prefixes account data with 8 bytes of the hash of `account:<name>`.
+const DISCRIMINATOR_LEN: usize = 8;
+
+/// GitHub caps usernames at 39 characters.
+const MAX_GITHUB_LEN: usize = 39;
+
+/// `InvalidGithubAccount` in the program's IDL.
+const INVALID_GITHUB_ACCOUNT: u32 = 6000;
+
+/// The two instructions of the prereq program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrereqAction {
+    /// `complete`: creates the `prereq` account
+    Submit,
+    /// `update`: changes the handle stored in it
+    Update,
+}
+
+/// What a prereq transaction is about to do, shown for confirmation before it is sent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrereqSummary {
+    pub action: PrereqAction,
+    pub signer: Pubkey,
+    pub prereq: Pubkey,
+    pub github: String,
+    pub fee: u64,
+    pub fee_payer: Pubkey,
+}
+
+/// What the `prereq` account stores, `PrereqAccount` in the IDL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrereqAccount {
+    /// GitHub handle as submitted
+    pub github: Vec<u8>,
+    /// The signer who submitted
+    pub key: Pubkey,
+}
+
+impl PrereqAccount {
+    pub fn discriminator() -> [u8; DISCRIMINATOR_LEN] {
+        let hash = hashv(&[b"account:PrereqAccount".as_slice()]);
+        let mut discriminator = [0; DISCRIMINATOR_LEN];
+        discriminator.copy_from_slice(&hash.to_bytes()[..DISCRIMINATOR_LEN]);
+
+        discriminator
+    }
+
+    /// Decodes account data: the discriminator, then the Borsh layout of the fields, a
+    /// `u32` little-endian length before the bytes of `github`. Anchor may leave unused
+    /// space at the end.
+    pub fn decode(data: &[u8]) -> Result<PrereqAccount, ToolkitError> {
+        let invalid =
+            |reason: &str| ToolkitError::Decode(format!("Invalid PrereqAccount: {}", reason));
+
+        let (discriminator, data) = data
+            .split_at_checked(DISCRIMINATOR_LEN)
+            .ok_or_else(|| invalid("too short for a discriminator"))?;
+        if discriminator != Self::discriminator() {
+            return Err(invalid("wrong discriminator, not a PrereqAccount"));
+        }
+
+        let (len, data) = data
+            .split_first_chunk::<4>()
+            .ok_or_else(|| invalid("github length is missing"))?;
+        let (github, data) = data
+            .split_at_checked(u32::from_le_bytes(*len) as usize)
+            .ok_or_else(|| invalid("github is cut short"))?;
+        let (key, _) = data
+            .split_first_chunk::<32>()
+            .ok_or_else(|| invalid("key is missing"))?;
+
+        Ok(PrereqAccount {
+            github: github.to_vec(),
+            key: Pubkey::new_from_array(*key),
+        })
+    }
+
+    /// The handle as text, invalid UTF-8 replaced.
+    pub fn github_handle(&self) -> String {
+        String::from_utf8_lossy(&self.github).into_owned()
+    }
+}
+
+/// The `prereq` account of `signer`, a PDA of the prereq program, with its bump. Every
+/// command looks the account up through here, so they all agree on it.
+pub fn find_prereq_address(signer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PREREQ_SEED, signer.as_ref()], &PREREQ_PROGRAM_ID)
+}
+
+/// What the `prereq` account of `signer` holds, none when it has not completed. An account
+/// at that address that the program does not own is an error.
+pub fn read_prereq(
+    client: &RpcClient,
+    signer: &Pubkey,
+) -> Result<Option<PrereqAccount>, ToolkitError> {
+    let (prereq, account) = fetch_prereq(client, signer)?;
+
+    match account {
+        None => Ok(None),
+        Some(account) if account.owner != PREREQ_PROGRAM_ID => Err(ToolkitError::Decode(format!(
+            "{} is owned by {}, not the prereq program",
+            prereq, account.owner
+        ))),
+        Some(account) => PrereqAccount::decode(&account.data).map(Some),
+    }
+}
+
+/// A row of a cohort roster: the handle a student gave, and their wallet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RosterEntry {
+    /// Line in the roster file, from 1
+    pub line: usize,
+    pub github: String,
+    pub wallet: Pubkey,
+}
+
+/// Where a roster row stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditStatus {
+    /// The prereq account holds the handle from the roster
+    Completed,
+    /// The wallet has no prereq account
+    Missing,
+    /// The prereq account holds another handle
+    GithubMismatch,
+    /// Something that is not a valid prereq account sits at the address
+    Invalid,
+}
+
+impl fmt::Display for AuditStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuditStatus::Completed => write!(f, "completed"),
+            AuditStatus::Missing => write!(f, "missing"),
+            AuditStatus::GithubMismatch => write!(f, "github-mismatch"),
+            AuditStatus::Invalid => write!(f, "invalid"),
+        }
+    }
+}
+
+/// The verdict on one roster row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditRow {
+    pub entry: RosterEntry,
+    pub prereq: Pubkey,
+    pub status: AuditStatus,
+    /// Handle stored on chain, when there is a readable one
+    pub stored_github: Option<String>,
+    /// Why the row is invalid
+    pub reason: Option<String>,
+}
+
+/// Splits a CSV line into its cells. A quoted cell may hold commas, with `""` standing for
+/// a quote inside it, as `--output csv` writes them.
+fn split_csv_line(line: &str) -> Result<Vec<String>, &'static str> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => cell.push(c),
+            (false, '"') if cell.trim().is_empty() => {
+                cell.clear();
+                quoted = true;
+            }
+            (false, ',') => cells.push(std::mem::take(&mut cell)),
+            (false, c) => cell.push(c),
+        }
+    }
+    if quoted {
+        return Err("a quoted cell is not closed");
+    }
+    cells.push(cell);
+
+    Ok(cells)
+}
+
+/// A roster header is exactly `github,wallet` or `github,pubkey`, in any case. Going by the
+/// second cell keeps a student whose handle is `github` from being taken for one.
+fn is_roster_header(cells: &[&str]) -> bool {
+    match cells {
+        [github, wallet] => {
+            github.eq_ignore_ascii_case("github")
+                && (wallet.eq_ignore_ascii_case("wallet") || wallet.eq_ignore_ascii_case("pubkey"))
+        }
+        _ => false,
+    }
+}
+
+/// Parses `github,pubkey` rows. Blank lines are skipped, and so is a header as the first
+/// row. Cells may be quoted.
+pub fn parse_roster(contents: &str) -> Result<Vec<RosterEntry>, ToolkitError> {
+    let mut entries = Vec::new();
+    let mut first_row = true;
+
+    for (index, line) in contents.lines().enumerate() {
+        let line_number = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let cells = split_csv_line(line).map_err(|reason| {
+            ToolkitError::InvalidInput(format!("Roster line {}: {}", line_number, reason))
+        })?;
+        let cells: Vec<&str> = cells.iter().map(|cell| cell.trim()).collect();
+
+        let is_header = first_row && is_roster_header(&cells);
+        first_row = false;
+        if is_header {
+            continue;
+        }
+
+        let [github, wallet] = cells[..] else {
+            return Err(ToolkitError::InvalidInput(format!(
+                "Roster line {}: expected github,pubkey, got {}",
+                line_number, line
+            )));
+        };
+        if github.is_empty() {
+            return Err(ToolkitError::InvalidInput(format!(
+                "Roster line {}: the GitHub handle is empty",
+                line_number
+            )));
+        }
+        let wallet = parse_pubkey(wallet).map_err(|e| {
+            ToolkitError::InvalidPubkey(format!("Roster line {}: {}", line_number, e))
+        })?;
+
+        entries.push(RosterEntry {
+            line: line_number,
+            github: github.to_string(),
+            wallet,
+        });
+    }
+
+    Ok(entries)
+}
+
+pub fn read_roster(path: &Path) -> Result<Vec<RosterEntry>, ToolkitError> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| ToolkitError::Io(format!("Cannot read roster {}: {}", path.display(), e)))?;
+
+    parse_roster(&contents)
+}
+
+/// Checks every roster row against its prereq account, fetched in batches.
+pub fn audit_roster(
+    client: &RpcClient,
+    roster: Vec<RosterEntry>,
+) -> Result<Vec<AuditRow>, ToolkitError> {
+    let mut rows = Vec::with_capacity(roster.len());
+
+    for batch in roster.chunks(solana::MAX_MULTIPLE_ACCOUNTS) {
+        let addresses: Vec<Pubkey> = batch
+            .iter()
+            .map(|entry| find_prereq_address(&entry.wallet).0)
+            .collect();
+        let accounts = client
+            .get_multiple_accounts_with_commitment(&addresses, client.commitment())?
+            .value;
+
+        for ((entry, prereq), account) in batch.iter().zip(addresses).zip(accounts) {
+            rows.push(audit_row(entry.clone(), prereq, account.as_ref()));
+        }
+    }
+
+    Ok(rows)
+}
+
+fn audit_row(entry: RosterEntry, prereq: Pubkey, account: Option<&Account>) -> AuditRow {
+    let decoded = match account {
+        None => Ok(None),
+        Some(account) if account.owner != PREREQ_PROGRAM_ID => Err(format!(
+            "owned by {}, not the prereq program",
+            account.owner
+        )),
+        Some(account) => PrereqAccount::decode(&account.data)
+            .map(Some)
+            .map_err(|e| e.to_string()),
+    };
+
+    let (status, stored_github, reason) = match decoded {
+        Ok(None) => (AuditStatus::Missing, None, None),
+        Err(reason) => (AuditStatus::Invalid, None, Some(reason)),
+        Ok(Some(stored)) if stored.key != entry.wallet => (
+            AuditStatus::Invalid,
+            Some(stored.github_handle()),
+            Some(format!("stores key {}", stored.key)),
+        ),
+        Ok(Some(stored)) => {
+            let handle = stored.github_handle();
+            // GitHub handles are case-insensitive
+            let status = if handle.eq_ignore_ascii_case(&entry.github) {
+                AuditStatus::Completed
+            } else {
+                AuditStatus::GithubMismatch
+            };
+            (status, Some(handle), None)
+        }
+    };
+
+    AuditRow {
+        entry,
+        prereq,
+        status,
+        stored_github,
+        reason,
+    }
+}
+
+/// A `prereq` account found by scanning the program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrereqEntry {
+    pub prereq: Pubkey,
+    pub account: PrereqAccount,
+}
+
+/// Every `PrereqAccount` the program owns, sorted by GitHub handle. Accounts are picked by
+/// their discriminator; those that still fail to decode are skipped with a warning.
+pub fn list_prereqs(client: &RpcClient) -> Result<Vec<PrereqEntry>, ToolkitError> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            0,
+            &PrereqAccount::discriminator(),
+        ))]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+
+    let mut entries = Vec::new();
+    for (prereq, account) in client.get_program_accounts_with_config(&PREREQ_PROGRAM_ID, config)? {
+        match PrereqAccount::decode(&account.data) {
+            Ok(account) => entries.push(PrereqEntry { prereq, account }),
+            Err(e) => eprintln!("Skipping undecodable prereq account {} ({})", prereq, e),
+        }
+    }
+    entries
+        .sort_by_cached_key(|entry| (entry.account.github_handle().to_lowercase(), entry.prereq));
+
+    Ok(entries)
+}
+
+/// GitHub handles stored by more than one account, lowercased since GitHub ignores case.
+pub fn duplicate_handles(entries: &[PrereqEntry]) -> Vec<String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for entry in entries {
+        *counts
+            .entry(entry.account.github_handle().to_lowercase())
+            .or_default() += 1;
+    }
+
+    let mut duplicates: Vec<String> = counts
+        .into_iter()
+        .filter(|(_, count)| *count > 1)
+        .map(|(handle, _)| handle)
+        .collect();
+    duplicates.sort();
+
+    duplicates
+}
+
+/// The `prereq` account of `signer` and what it holds, none when it has not submitted.
+pub fn fetch_prereq(
+    client: &RpcClient,
+    signer: &Pubkey,
+) -> Result<(Pubkey, Option<Account>), ToolkitError> {
+    let (prereq, _) = find_prereq_address(signer);
+    let account = client
+        .get_account_with_commitment(&prereq, client.commitment())?
+        .value;
+
+    Ok((prereq, account))
+}
+
+/// A GitHub username: letters, digits and single hyphens, not at either end.
+pub fn validate_github(handle: &str) -> Result<(), ToolkitError> {
+    let valid = !handle.is_empty()
+        && handle.len() <= MAX_GITHUB_LEN
+        && handle
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+        && !handle.starts_with('-')
+        && !handle.ends_with('-')
+        && !handle.contains("--");
+
+    if !valid {
+        return Err(ToolkitError::InvalidInput(format!(
+            "Invalid GitHub handle {}: expected up to {} letters, digits or single hyphens",
+            handle, MAX_GITHUB_LEN
+        )));
+    }
+
+    Ok(())
+}
+
+/// Readable messages for the custom errors of the prereq program.
+fn program_error(code: u32, github: &str) -> Option<String> {
+    match code {
+        INVALID_GITHUB_ACCOUNT => Some(format!(
+            "The prereq program rejected GitHub handle {}: Invalid Github account",
+            github
+        )),
+        _ => None,
+    }
+}
+
+/// Sends `complete` or `update` for `signer` with its GitHub handle. `complete` needs the
+/// `prereq` account not to exist yet, `update` needs it to. Nothing is sent until `confirm`
+/// accepts the summary, nothing is broadcast with `simulate`.
+pub fn send_prereq(
+    signer: Wallet,
+    action: PrereqAction,
+    github: &str,
+    fee_payer: Option<Wallet>,
+    client: &RpcClient,
+    simulate: bool,
+    confirm: impl FnOnce(&PrereqSummary) -> Result<(), ToolkitError>,
+) -> Result<(Outcome, PrereqSummary), ToolkitError> {
+    validate_github(github)?;
+
+    let keypair = signer.keypair()?;
+    let fee_payer = fee_payer.map(|wallet| wallet.keypair()).transpose()?;
+    let payer = fee_payer.as_ref().unwrap_or(&keypair);
+    let (prereq, account) = fetch_prereq(client, &keypair.pubkey())?;
+
+    let submitted = account.is_some();
+    match action {
+        PrereqAction::Submit if submitted => {
+            return Err(ToolkitError::AlreadyExists(format!(
+                "Prereq of {} already submitted at {}, use `prereq update` to change it",
+                keypair.pubkey(),
+                prereq
+            )))
+        }
+        PrereqAction::Update if !submitted => {
+            return Err(ToolkitError::NotFound(format!(
+                "No prereq submitted by {}, use `prereq submit` first",
+                keypair.pubkey()
+            )))
+        }
+        _ => {}
+    }
+
+    // Get recent blockhash
+    let blockhash = client.get_latest_blockhash()?;
+
+    let accounts = [&keypair.pubkey(), &prereq, &system_program::id()];
+    let github_bytes = github.as_bytes().to_vec();
+    let signers = solana::signers(&keypair, payer);
+    let transaction = match action {
+        PrereqAction::Submit => WbaPrereqProgram::complete(
+            &accounts,
+            &CompleteArgs {
+                github: github_bytes,
+            },
+            Some(&payer.pubkey()),
+            &signers,
+            blockhash,
+        ),
+        PrereqAction::Update => WbaPrereqProgram::update(
+            &accounts,
+            &UpdateArgs {
+                github: github_bytes,
+            },
+            Some(&payer.pubkey()),
+            &signers,
+            blockhash,
+        ),
+    };
+
+    let summary = PrereqSummary {
+        action,
+        signer: keypair.pubkey(),
+        prereq,
+        github: github.to_string(),
+        fee: client.get_fee_for_message(&transaction.message)?,
+        fee_payer: payer.pubkey(),
+    };
+    confirm(&summary)?;
+
+    // Send the transaction
+    let outcome = solana::submit_with_errors(client, &transaction, simulate, |code| {
+        program_error(code, github)
+    })?;
+
+    Ok((outcome, summary))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_github() {
+        let longest = "a".repeat(39);
+        let too_long = "a".repeat(40);
+
+        for handle in ["kox", "a", "octo-cat", "User123", longest.as_str()] {
+            assert!(validate_github(handle).is_ok(), "{}", handle);
+        }
+        for handle in [
+            "",
+            "-kox",
+            "kox-",
+            "octo--cat",
+            "kox!",
+            "ko x",
+            too_long.as_str(),
+        ] {
+            assert_eq!(
+                validate_github(handle).unwrap_err().exit_code(),
+                12,
+                "{}",
+                handle
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_prereq_account() {
+        let key = Pubkey::new_unique();
+        let mut data = vec![63, 77, 126, 195, 95, 106, 211, 120];
+        data.extend(3u32.to_le_bytes());
+        data.extend(b"kox");
+        data.extend(key.to_bytes());
+
+        let account = PrereqAccount::decode(&data).unwrap();
+        assert_eq!(account.github_handle(), "kox");
+        assert_eq!(account.key, key);
+
+        // Space the program reserved but did not use
+        data.extend([0; 16]);
+        assert_eq!(PrereqAccount::decode(&data).unwrap(), account);
+
+        let cut = PrereqAccount::decode(&data[..40]).unwrap_err();
+        assert_eq!(cut.exit_code(), 14);
+        assert!(cut.to_string().contains("key is missing"));
+
+        data[0] ^= 1;
+        assert!(PrereqAccount::decode(&data)
+            .unwrap_err()
+            .to_string()
+            .contains("wrong discriminator"));
+        assert!(PrereqAccount::decode(&[]).is_err());
+    }
+
+    fn prereq_account(github: &[u8], key: &Pubkey) -> Account {
+        let mut data = PrereqAccount::discriminator().to_vec();
+        data.extend((github.len() as u32).to_le_bytes());
+        data.extend(github);
+        data.extend(key.to_bytes());
+
+        Account {
+            lamports: 1_000_000,
+            data,
+            owner: PREREQ_PROGRAM_ID,
+            ..Account::default()
+        }
+    }
+
+    #[test]
+    fn test_duplicate_handles() {
+        let entry = |github: &[u8]| PrereqEntry {
+            prereq: Pubkey::new_unique(),
+            account: PrereqAccount {
+                github: github.to_vec(),
+                key: Pubkey::new_unique(),
+            },
+        };
+
+        let entries = vec![
+            entry(b"kox"),
+            entry(b"octocat"),
+            entry(b"Kox"),
+            entry(b"zed"),
+            entry(b"zed"),
+        ];
+        assert_eq!(duplicate_handles(&entries), vec!["kox", "zed"]);
+        assert!(duplicate_handles(&entries[..2]).is_empty());
+    }
+
+    #[test]
+    fn test_parse_roster() {
+        let wallet = Pubkey::new_unique();
+        let roster = format!(
+            "github,wallet\n\nkox, {}\n\"octo-cat\",\"{}\"\n",
+            wallet, wallet
+        );
+
+        let entries = parse_roster(&roster).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].line, 3);
+        assert_eq!(entries[0].github, "kox");
+        assert_eq!(entries[1].github, "octo-cat");
+        assert_eq!(entries[1].wallet, wallet);
+
+        assert_eq!(parse_roster("kox").unwrap_err().exit_code(), 12);
+        assert_eq!(parse_roster(",abc").unwrap_err().exit_code(), 12);
+        assert_eq!(parse_roster("\"kox,abc").unwrap_err().exit_code(), 12);
+        let error = parse_roster("kox,not-a-pubkey").unwrap_err();
+        assert_eq!(error.exit_code(), 13);
+        assert!(error.to_string().contains("line 1"));
+    }
+
+    #[test]
+    fn test_parse_roster_quoting_and_header() {
+        let wallet = Pubkey::new_unique();
+
+        // A quoted comma stays in its cell, and a quote is doubled
+        let entries = parse_roster(&format!(
+            "\"Doe, Jane\",{}\n\"say \"\"hi\"\"\",{}",
+            wallet, wallet
+        ))
+        .unwrap();
+        assert_eq!(entries[0].github, "Doe, Jane");
+        assert_eq!(entries[0].wallet, wallet);
+        assert_eq!(entries[1].github, "say \"hi\"");
+
+        // A student called github is a row, not a header
+        let entries = parse_roster(&format!("github,{}\nkox,{}", wallet, wallet)).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].github, "github");
+
+        let entries = parse_roster(&format!("GitHub,Pubkey\nkox,{}", wallet)).unwrap();
+        assert_eq!(entries.len(), 1);
+        // Only the first row can be a header
+        assert!(parse_roster(&format!("kox,{}\ngithub,wallet", wallet)).is_err());
+    }
+
+    #[test]
+    fn test_audit_row() {
+        let wallet = Pubkey::new_unique();
+        let entry = RosterEntry {
+            line: 1,
+            github: "Kox".to_string(),
+            wallet,
+        };
+        let (prereq, _) = find_prereq_address(&wallet);
+        let status = |account: Option<&Account>| audit_row(entry.clone(), prereq, account).status;
+
+        assert_eq!(status(None), AuditStatus::Missing);
+        assert_eq!(
+            status(Some(&prereq_account(b"kox", &wallet))),
+            AuditStatus::Completed
+        );
+
+        let mismatch = audit_row(
+            entry.clone(),
+            prereq,
+            Some(&prereq_account(b"someone", &wallet)),
+        );
+        assert_eq!(mismatch.status, AuditStatus::GithubMismatch);
+        assert_eq!(mismatch.stored_github.as_deref(), Some("someone"));
+
+        let foreign = Account {
+            owner: Pubkey::new_unique(),
+            ..prereq_account(b"kox", &wallet)
+        };
+        assert_eq!(status(Some(&foreign)), AuditStatus::Invalid);
+        assert_eq!(
+            status(Some(&prereq_account(b"kox", &Pubkey::new_unique()))),
+            AuditStatus::Invalid
+        );
+        assert_eq!(AuditStatus::GithubMismatch.to_string(), "github-mismatch");
+    }
+
+    #[test]
+    fn test_prereq_address() {
+        let signer = Pubkey::new_unique();
+
+        let (prereq, bump) = find_prereq_address(&signer);
+        assert_eq!(
+            Pubkey::create_program_address(
+                &[PREREQ_SEED, signer.as_ref(), &[bump]],
+                &PREREQ_PROGRAM_ID
+            ),
+            Ok(prereq)
+        );
+        assert_ne!(prereq, find_prereq_address(&Pubkey::new_unique()).0);
+        assert!(program_error(INVALID_GITHUB_ACCOUNT, "kox")
+            .unwrap()
+            .contains("kox"));
+        assert_eq!(program_error(6001, "kox"), None);
+    }
+}
@@ -0,0 +1,448 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use bip39::Mnemonic;
+use serde::{Deserialize, Serialize};
+use solana_sdk::signer::Signer;
+
+use super::crypto::{decrypt_secret, encrypt_secret, EncryptedSecret, PassphraseSource};
+use super::error::ToolkitError;
+use super::meta::{Origin, WalletMeta};
+use super::mnemonic::{
+    check_account_index, generate_mnemonic, keypair_from_seed, parse_mnemonic,
+};
+use super::output::verbose;
+use super::store::{StoreBatch, WalletStore};
+use super::wallet::{ensure_name_available, replace_wallet_record, verify_passphrase, Wallet};
+
+/// A seed wallet: only the encrypted BIP39 seed and the indexes derived so far.
+/// Child secrets are re-derived on every read and never written to the store.
+#[derive(Serialize, Deserialize, Debug)]
+struct StoredSeed {
+    seed: EncryptedSecret,
+    accounts: BTreeSet<u32>,
+    /// Metadata of each derived child, keyed by index
+    #[serde(default)]
+    children: BTreeMap<u32, WalletMeta>,
+    /// Metadata of the seed wallet itself
+    #[serde(default)]
+    meta: WalletMeta,
+}
+
+impl StoredSeed {
+    fn child_meta(&self, index: u32) -> WalletMeta {
+        self.children
+            .get(&index)
+            .cloned()
+            .unwrap_or_else(|| WalletMeta {
+                origin: Origin::Derived,
+                ..WalletMeta::default()
+            })
+    }
+}
+
+pub fn get_seed_key(name: &str) -> String {
+    format!("seed:{}", name)
+}
+
+pub fn child_name(parent: &str, index: u32) -> String {
+    format!("{}/{}", parent, index)
+}
+
+/// Splits a child wallet name like `treasury/3` into its parent and index.
+pub fn parse_child_name(name: &str) -> Option<(&str, u32)> {
+    let (parent, index) = name.rsplit_once('/')?;
+
+    Some((parent, index.parse().ok()?))
+}
+
+fn read_stored_seed(db: &dyn WalletStore, name: &str) -> Result<Option<StoredSeed>, ToolkitError> {
+    match db.get(&get_seed_key(name))? {
+        Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+        None => Ok(None),
+    }
+}
+
+fn write_stored_seed(
+    db: &dyn WalletStore,
+    name: &str,
+    stored: &StoredSeed,
+) -> Result<(), ToolkitError> {
+    db.put(&get_seed_key(name), &serde_json::to_vec(stored)?)?;
+
+    Ok(())
+}
+
+pub fn save_seed_to_db(
+    db: &dyn WalletStore,
+    name: &str,
+    seed: &[u8],
+    passphrase: &str,
+    force: bool,
+    origin: Origin,
+) -> Result<(), ToolkitError> {
+    ensure_name_available(db, name, force)?;
+    verify_passphrase(db, passphrase)?;
+
+    let stored = StoredSeed {
+        seed: encrypt_secret(seed, passphrase)?,
+        accounts: BTreeSet::new(),
+        children: BTreeMap::new(),
+        meta: WalletMeta::new(origin),
+    };
+
+    verbose!("Saving seed wallet to DB with key: {}", get_seed_key(name));
+    let mut batch = StoreBatch::default();
+    replace_wallet_record(
+        &mut batch,
+        name,
+        &get_seed_key(name),
+        &serde_json::to_vec(&stored)?,
+    );
+    db.write(batch)
+}
+
+pub fn generate_seed_wallet(
+    db: &dyn WalletStore,
+    name: &str,
+    passphrase: &str,
+    word_count: usize,
+    seed_passphrase: &str,
+    force: bool,
+) -> Result<Mnemonic, ToolkitError> {
+    // Fail before a seed phrase is generated that would never be saved
+    ensure_name_available(db, name, force)?;
+
+    let mnemonic = generate_mnemonic(word_count)?;
+
+    save_seed_to_db(
+        db,
+        name,
+        &mnemonic.to_seed(seed_passphrase),
+        passphrase,
+        force,
+        Origin::Generated,
+    )?;
+
+    Ok(mnemonic)
+}
+
+pub fn import_seed_wallet(
+    db: &dyn WalletStore,
+    phrase: &str,
+    name: &str,
+    passphrase: &str,
+    seed_passphrase: &str,
+    force: bool,
+) -> Result<(), ToolkitError> {
+    let mnemonic = parse_mnemonic(phrase)?;
+
+    save_seed_to_db(
+        db,
+        name,
+        &mnemonic.to_seed(seed_passphrase),
+        passphrase,
+        force,
+        Origin::Derived,
+    )
+}
+
+/// Derives a child account of the seed wallet `parent` and records its index.
+/// Without an explicit index the one after the highest derived so far is taken.
+pub fn derive_child_wallet(
+    db: &dyn WalletStore,
+    parent: &str,
+    index: Option<u32>,
+    passphrase: &(impl PassphraseSource + ?Sized),
+) -> Result<(String, Wallet), ToolkitError> {
+    let mut stored = read_stored_seed(db, parent)?
+        .ok_or_else(|| ToolkitError::NotFound(format!("Seed wallet {} not found", parent)))?;
+
+    let index = match index {
+        Some(index) => index,
+        None => match stored.accounts.last() {
+            Some(last) => last.checked_add(1).ok_or_else(|| {
+                ToolkitError::InvalidInput(format!(
+                    "{} has no account index left after {}, pass --index",
+                    parent, last
+                ))
+            })?,
+            None => 0,
+        },
+    };
+    check_account_index(index)?;
+
+    let seed = decrypt_secret(&stored.seed, &passphrase.passphrase()?)?;
+    let keypair = keypair_from_seed(&seed, index)?;
+
+    // Only the index is persisted, the child secret is derived on demand
+    stored.accounts.insert(index);
+    stored
+        .children
+        .entry(index)
+        .or_insert_with(|| WalletMeta::new(Origin::Derived));
+    write_stored_seed(db, parent, &stored)?;
+
+    let wallet = Wallet {
+        pubkey: keypair.pubkey().to_string(),
+        secret_key: keypair.to_bytes().to_vec().into(),
+    };
+
+    Ok((child_name(parent, index), wallet))
+}
+
+/// Reads the derived child `parent/index`, or `None` if `parent` is not a seed wallet.
+pub fn read_child_wallet(
+    db: &dyn WalletStore,
+    parent: &str,
+    index: u32,
+    passphrase: &(impl PassphraseSource + ?Sized),
+) -> Result<Option<Wallet>, ToolkitError> {
+    let stored = match read_stored_seed(db, parent)? {
+        Some(stored) => stored,
+        None => return Ok(None),
+    };
+
+    if !stored.accounts.contains(&index) {
+        return Err(ToolkitError::NotFound(format!(
+            "Account {} has not been derived yet",
+            child_name(parent, index)
+        )));
+    }
+
+    let seed = decrypt_secret(&stored.seed, &passphrase.passphrase()?)?;
+    let keypair = keypair_from_seed(&seed, index)?;
+
+    Ok(Some(Wallet {
+        pubkey: keypair.pubkey().to_string(),
+        secret_key: keypair.to_bytes().to_vec().into(),
+    }))
+}
+
+/// Every seed wallet with its own metadata, read without decrypting the seeds.
+pub fn list_seed_wallets(db: &dyn WalletStore) -> Result<Vec<(String, WalletMeta)>, ToolkitError> {
+    let mut seeds = Vec::new();
+    let prefix = "seed:";
+
+    for (key, value) in db.scan(prefix)? {
+        let name = key[prefix.len()..].to_string();
+        match serde_json::from_slice::<StoredSeed>(&value) {
+            Ok(stored) => seeds.push((name, stored.meta)),
+            Err(e) => eprintln!(
+                "Skipping undecodable seed wallet {} ({}), run `db check`",
+                name, e
+            ),
+        }
+    }
+
+    Ok(seeds)
+}
+
+pub fn read_seed_meta(db: &dyn WalletStore, name: &str) -> Result<Option<WalletMeta>, ToolkitError> {
+    Ok(read_stored_seed(db, name)?.map(|stored| stored.meta))
+}
+
+pub fn update_seed_meta(
+    db: &dyn WalletStore,
+    name: &str,
+    update: impl FnOnce(&mut WalletMeta),
+) -> Result<(), ToolkitError> {
+    let mut stored = read_stored_seed(db, name)?
+        .ok_or_else(|| ToolkitError::NotFound(format!("Seed wallet {} not found", name)))?;
+
+    update(&mut stored.meta);
+
+    write_stored_seed(db, name, &stored)
+}
+
+pub fn read_child_meta(
+    db: &dyn WalletStore,
+    parent: &str,
+    index: u32,
+) -> Result<Option<WalletMeta>, ToolkitError> {
+    Ok(read_stored_seed(db, parent)?
+        .filter(|stored| stored.accounts.contains(&index))
+        .map(|stored| stored.child_meta(index)))
+}
+
+pub fn update_child_meta(
+    db: &dyn WalletStore,
+    parent: &str,
+    index: u32,
+    update: impl FnOnce(&mut WalletMeta),
+) -> Result<(), ToolkitError> {
+    let mut stored = read_stored_seed(db, parent)?
+        .filter(|stored| stored.accounts.contains(&index))
+        .ok_or_else(|| {
+            ToolkitError::NotFound(format!("Wallet {} not found", child_name(parent, index)))
+        })?;
+
+    let mut meta = stored.child_meta(index);
+    update(&mut meta);
+    stored.children.insert(index, meta);
+
+    write_stored_seed(db, parent, &stored)
+}
+
+/// Checks a raw seed record, and when a passphrase is given that its seed decrypts
+/// and derives every recorded child.
+pub fn validate_seed_record(value: &[u8], passphrase: Option<&str>) -> Result<(), ToolkitError> {
+    let stored: StoredSeed = serde_json::from_slice(value)
+        .map_err(|e| ToolkitError::Decode(format!("undecodable seed record: {}", e)))?;
+
+    if let Some(passphrase) = passphrase {
+        let seed = decrypt_secret(&stored.seed, passphrase)?;
+        for &index in &stored.accounts {
+            keypair_from_seed(&seed, index)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The encrypted seed of a raw seed record, `None` when it does not decode.
+pub fn seed_record_secret(value: &[u8]) -> Option<EncryptedSecret> {
+    serde_json::from_slice::<StoredSeed>(value)
+        .ok()
+        .map(|stored| stored.seed)
+}
+
+/// Re-derives every recorded child of every seed wallet, with its child name and metadata.
+/// Seed wallets that cannot be decoded or decrypted are reported and skipped. The
+/// passphrase is only asked for when there is a seed wallet.
+pub fn list_child_wallets(
+    db: &dyn WalletStore,
+    passphrase: &(impl PassphraseSource + ?Sized),
+) -> Result<Vec<(String, Wallet, WalletMeta)>, ToolkitError> {
+    let mut children = Vec::new();
+    let prefix = "seed:";
+
+    let seeds = db.scan(prefix)?;
+    if seeds.is_empty() {
+        return Ok(children);
+    }
+    let passphrase = passphrase.passphrase()?;
+
+    for (key, value) in seeds {
+        let parent = key[prefix.len()..].to_string();
+        let stored: StoredSeed = match serde_json::from_slice(&value) {
+            Ok(stored) => stored,
+            Err(e) => {
+                eprintln!(
+                    "Skipping undecodable seed wallet {} ({}), run `db check`",
+                    parent, e
+                );
+                continue;
+            }
+        };
+
+        let seed = match decrypt_secret(&stored.seed, &passphrase) {
+            Ok(seed) => seed,
+            Err(e) => {
+                eprintln!("Could not decrypt seed wallet {}: {}", parent, e);
+                continue;
+            }
+        };
+
+        for &index in &stored.accounts {
+            let keypair = match keypair_from_seed(&seed, index) {
+                Ok(keypair) => keypair,
+                Err(e) => {
+                    eprintln!("Could not derive {}: {}", child_name(&parent, index), e);
+                    continue;
+                }
+            };
+            let wallet = Wallet {
+                pubkey: keypair.pubkey().to_string(),
+                secret_key: keypair.to_bytes().to_vec().into(),
+            };
+            children.push((child_name(&parent, index), wallet, stored.child_meta(index)));
+        }
+    }
+
+    Ok(children)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mnemonic::MAX_ACCOUNT_INDEX;
+    use crate::utils::store::MemoryStore;
+
+    const PASSPHRASE: &str = "test passphrase";
+
+    #[test]
+    fn test_derive_children_from_seed() {
+        let db = MemoryStore::default();
+
+        let mnemonic = generate_mnemonic(12).unwrap();
+        import_seed_wallet(
+            &db,
+            &mnemonic.to_string(),
+            "treasury",
+            PASSPHRASE,
+            "",
+            false,
+        )
+        .unwrap();
+
+        let (first_name, first) = derive_child_wallet(&db, "treasury", None, PASSPHRASE).unwrap();
+        let (second_name, second) = derive_child_wallet(&db, "treasury", None, PASSPHRASE).unwrap();
+        assert_eq!(first_name, "treasury/0");
+        assert_eq!(second_name, "treasury/1");
+        assert_ne!(first.pubkey, second.pubkey);
+
+        // Children match the plain mnemonic derivation for the same index
+        let expected = keypair_from_seed(&mnemonic.to_seed(""), 1).unwrap();
+        assert_eq!(second.pubkey, expected.pubkey().to_string());
+
+        let read = read_child_wallet(&db, "treasury", 0, PASSPHRASE).unwrap();
+        assert_eq!(read, Some(first));
+        assert!(read_child_wallet(&db, "treasury", 7, PASSPHRASE).is_err());
+        assert_eq!(
+            read_child_wallet(&db, "missing", 0, PASSPHRASE).unwrap(),
+            None
+        );
+
+        let children = list_child_wallets(&db, PASSPHRASE).unwrap();
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[0].2.origin, Origin::Derived);
+
+        update_child_meta(&db, "treasury", 1, |meta| {
+            meta.tags.insert("payer".to_string());
+        })
+        .unwrap();
+        let meta = read_child_meta(&db, "treasury", 1).unwrap().unwrap();
+        assert!(meta.tags.contains("payer"));
+        assert!(update_child_meta(&db, "treasury", 9, |_| {}).is_err());
+    }
+
+    #[test]
+    fn test_derive_index_stays_hardened() {
+        let db = MemoryStore::default();
+
+        let phrase = generate_mnemonic(12).unwrap().to_string();
+        import_seed_wallet(&db, &phrase, "treasury", PASSPHRASE, "", false).unwrap();
+
+        let error = derive_child_wallet(&db, "treasury", Some(1 << 31), PASSPHRASE).unwrap_err();
+        assert!(matches!(error, ToolkitError::InvalidInput(_)));
+        assert!(derive_child_wallet(&db, "treasury", Some(u32::MAX), PASSPHRASE).is_err());
+
+        let (name, _) =
+            derive_child_wallet(&db, "treasury", Some(MAX_ACCOUNT_INDEX), PASSPHRASE).unwrap();
+        assert_eq!(name, format!("treasury/{}", MAX_ACCOUNT_INDEX));
+
+        // The next index would leave the hardened range, and nothing is recorded for it
+        assert!(matches!(
+            derive_child_wallet(&db, "treasury", None, PASSPHRASE),
+            Err(ToolkitError::InvalidInput(_))
+        ));
+        assert_eq!(list_child_wallets(&db, PASSPHRASE).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_child_name() {
+        assert_eq!(parse_child_name("treasury/3"), Some(("treasury", 3)));
+        assert_eq!(parse_child_name("treasury"), None);
+        assert_eq!(parse_child_name("treasury/main"), None);
+    }
+}
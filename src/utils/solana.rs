@@ -1,169 +1,625 @@
-use std::str::FromStr;
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
 
-use solana_client::rpc_client::RpcClient;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    client_error::ClientError,
+    rpc_client::RpcClient,
+    rpc_config::{RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig},
+};
 use solana_sdk::{
-    message::Message, pubkey::Pubkey, signature::Keypair, signer::Signer,
-    system_instruction::transfer, transaction::Transaction,
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    message::Message,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    system_instruction::transfer,
+    transaction::{Transaction, TransactionError},
 };
 
+use super::amount::Amount;
+use super::cluster::{Cluster, Commitment};
+use super::error::ToolkitError;
+use super::fees::ComputeBudget;
+use super::output::verbose;
+use super::token::{self, TokenAccount};
 use super::wallet::Wallet;
 
-pub fn airdop(wallet: Wallet, rpc_url: &str) {
-    // Connected to Solana Devnet RPC Client
-    let client = RpcClient::new(rpc_url);
-
-    // We will create a keypair from our wallet struct
-    let keypair = Keypair::from_bytes(&wallet.secret_key).expect("Invalid keypair");
-
-    // We're going to claim 2 devnet SOL tokens (2 billion lamports)
-    match client.request_airdrop(&keypair.pubkey(), 2_000_000_000u64) {
-        Ok(s) => {
-            println!("Success! Check out your TX here:");
-            println!(
-                "https://explorer.solana.com/tx/{}?cluster=devnet",
-                s.to_string()
-            );
-        }
-        Err(e) => println!("Oops, something went wrong: {}", e.to_string()),
-    };
+/// Lamports requested by `airdop` when no amount is given: 2 SOL.
+pub const AIRDROP_LAMPORTS: u64 = 2_000_000_000;
+
+/// Wait before the first retry of a rate-limited airdrop, doubled after each attempt.
+const AIRDROP_BACKOFF: Duration = Duration::from_secs(1);
+/// How long an airdrop gets to reach the requested commitment.
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+const CONFIRM_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Token accounts closed by a single transaction, small enough to fit their transfers too.
+const TOKEN_ACCOUNTS_PER_TX: usize = 4;
+
+/// `getMultipleAccounts` returns at most this many accounts per request.
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// How transactions are priced, and whether they are broadcast at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SendOptions {
+    pub compute_budget: ComputeBudget,
+    /// Simulate instead of broadcasting, see `submit`
+    pub simulate: bool,
 }
 
-pub fn transfer_sol(sender: Wallet, receiver: &str, amount: u64, cluster_url: &str) {
-    // Connected to Solana Devnet RPC Client
-    let client = RpcClient::new(cluster_url);
+/// What became of a transaction: broadcast, or only run against the cluster's state.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Sent(Signature),
+    Simulated(Simulation),
+}
 
-    let sender_keypair = Keypair::from_bytes(&sender.secret_key)
-        .expect("Failed to create a Keypair from the sender Wallet");
-    let receiver_pubkey =
-        Pubkey::from_str(receiver).expect("Failed to create Pubkey from receiver Wallet");
+/// What `simulateTransaction` says a transaction would do. Nothing is broadcast.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Simulation {
+    pub units_consumed: Option<u64>,
+    pub logs: Vec<String>,
+    /// Every account of the transaction, with its lamports before and after
+    pub balance_changes: Vec<BalanceChange>,
+    pub fee: u64,
+}
 
-    // Get recent blockhash
-    let recent_blockhash = client
-        .get_latest_blockhash()
-        .expect("Failed to get recent blockhash");
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BalanceChange {
+    pub pubkey: Pubkey,
+    pub before: u64,
+    /// Zero when the transaction deletes the account
+    pub after: u64,
+}
 
-    // Let's transfer 0.1 SOL
-    let transaction = Transaction::new_signed_with_payer(
-        &[transfer(&sender_keypair.pubkey(), &receiver_pubkey, amount)], // 100_000_000
-        Some(&sender_keypair.pubkey()),
-        &vec![&sender_keypair],
-        recent_blockhash,
-    );
+impl BalanceChange {
+    pub fn delta(&self) -> i128 {
+        i128::from(self.after) - i128::from(self.before)
+    }
+}
 
-    // Send the transaction
-    let signature = client
-        .send_and_confirm_transaction(&transaction)
-        .expect("Failed to send transaction");
-
-    // Print our transaction out
-    println!(
-        "Success! Check out your TX here: https://explorer.solana.com/tx/{}/?cluster=devnet",
-        signature
-    );
+/// What `clean_wallet` moved, and what it cost.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sweep {
+    /// The final SOL transfer
+    pub outcome: Outcome,
+    /// Balance of the wallet once its token accounts were closed
+    pub balance: u64,
+    /// Fees of every transaction of the sweep
+    pub fee: u64,
+    /// Lamports sent to the recipient
+    pub amount: u64,
+    pub token_accounts: Vec<TokenAccount>,
+    /// The transactions closing the token accounts
+    pub token_outcomes: Vec<Outcome>,
 }
 
-pub fn clean_wallet(from: Wallet, to: &str, cluster_url: &str) {
-    // Connected to Solana Devnet RPC Client
-    let client = RpcClient::new(cluster_url);
+/// What a sweep is about to do, shown for confirmation before anything is signed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepPlan {
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub balance: u64,
+    /// Token accounts to close, their rent is swept along
+    pub token_accounts: Vec<TokenAccount>,
+    /// Lamports the recipient should get, less the rent of any token account the wallet
+    /// has to create for it
+    pub amount: u64,
+    pub fee: u64,
+    pub fee_payer: Pubkey,
+}
 
-    // Deserialize the JSON to a Wallet struct
-    let keypair = Keypair::from_bytes(&from.secret_key)
-        .expect("Failed to create a Keypair from the sender Wallet");
+/// What a transfer is about to do, shown for confirmation before it is signed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransferSummary {
+    pub from: Pubkey,
+    pub to: Pubkey,
+    /// Lamports the recipient gets
+    pub amount: u64,
+    pub fee: u64,
+    pub fee_payer: Pubkey,
+}
 
-    // Define our WBA public key
-    let to_pubkey = Pubkey::from_str(to).expect("Failed to create Pubkey from receiver Wallet ");
+/// Client for `cluster`, waiting for transactions to reach `commitment`.
+pub fn rpc_client(cluster: &Cluster, commitment: Commitment) -> RpcClient {
+    RpcClient::new_with_commitment(cluster.url().to_string(), commitment.into())
+}
 
-    // Let's check the balance
-    let balance = client
-        .get_balance(&keypair.pubkey())
-        .expect("Failed to get balance");
-    // Print our transaction out
-    println!("Left {} lamports!", balance);
+pub fn get_balance(client: &RpcClient, pubkey: &Pubkey) -> Result<u64, ToolkitError> {
+    Ok(client.get_balance(pubkey)?)
+}
+
+/// The account stored at `pubkey`, an error when there is none.
+pub fn get_account(client: &RpcClient, pubkey: &Pubkey) -> Result<Account, ToolkitError> {
+    client
+        .get_account_with_commitment(pubkey, client.commitment())?
+        .value
+        .ok_or_else(|| ToolkitError::NotFound(format!("No account at {}", pubkey)))
+}
+
+/// Requests `lamports` from the faucet and waits until the airdrop reaches the client's
+/// commitment. Rate-limited requests are retried `retries` times with exponential backoff.
+pub fn airdop(
+    pubkey: &Pubkey,
+    lamports: u64,
+    retries: u32,
+    client: &RpcClient,
+) -> Result<Signature, ToolkitError> {
+    let mut delay = AIRDROP_BACKOFF;
+    let mut attempt = 0;
+
+    let signature = loop {
+        match client.request_airdrop(pubkey, lamports) {
+            Ok(signature) => break signature,
+            Err(e) if is_rate_limited(&e) && attempt < retries => {
+                attempt += 1;
+                eprintln!(
+                    "Faucet rate limit hit, retrying in {}s ({}/{})",
+                    delay.as_secs(),
+                    attempt,
+                    retries
+                );
+                thread::sleep(delay);
+                delay *= 2;
+            }
+            Err(e) if is_rate_limited(&e) => {
+                return Err(ToolkitError::RateLimited(format!(
+                    "The faucet is rate limiting airdrops, gave up after {} retries: {}",
+                    retries, e
+                )));
+            }
+            Err(e) => return Err(e.into()),
+        }
+    };
+
+    wait_for_confirmation(client, &signature)?;
+
+    Ok(signature)
+}
+
+/// Broadcasts `transaction` and waits for the client's commitment or, with `simulate`, only
+/// runs it. A simulation that fails is an error, its logs are printed to stderr.
+pub fn submit(
+    client: &RpcClient,
+    transaction: &Transaction,
+    simulate: bool,
+) -> Result<Outcome, ToolkitError> {
+    submit_with_errors(client, transaction, simulate, |_| None)
+}
+
+/// Like `submit`, with `program_error` turning the custom error codes of the program called
+/// into readable messages. Those failures are invalid input rather than RPC errors.
+pub fn submit_with_errors(
+    client: &RpcClient,
+    transaction: &Transaction,
+    simulate: bool,
+    program_error: impl Fn(u32) -> Option<String>,
+) -> Result<Outcome, ToolkitError> {
+    let readable = |e: &TransactionError| match e {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            program_error(*code).map(ToolkitError::InvalidInput)
+        }
+        _ => None,
+    };
+
+    if !simulate {
+        return match client.send_and_confirm_transaction(transaction) {
+            Ok(signature) => Ok(Outcome::Sent(signature)),
+            Err(e) => Err(e
+                .get_transaction_error()
+                .and_then(|e| readable(&e))
+                .unwrap_or_else(|| e.into())),
+        };
+    }
+
+    let keys = &transaction.message.account_keys;
+    let before = client.get_multiple_accounts(keys)?;
+    let config = RpcSimulateTransactionConfig {
+        sig_verify: true,
+        commitment: Some(client.commitment()),
+        accounts: Some(RpcSimulateTransactionAccountsConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            addresses: keys.iter().map(Pubkey::to_string).collect(),
+        }),
+        ..RpcSimulateTransactionConfig::default()
+    };
+    let result = client
+        .simulate_transaction_with_config(transaction, config)?
+        .value;
+    let logs = result.logs.unwrap_or_default();
+
+    if let Some(e) = result.err {
+        for log in &logs {
+            eprintln!("  {}", log);
+        }
+        return Err(readable(&e)
+            .unwrap_or_else(|| ToolkitError::Rpc(format!("Simulated transaction failed: {}", e))));
+    }
+
+    let after = result.accounts.unwrap_or_default();
+    let balance_changes = keys
+        .iter()
+        .enumerate()
+        .map(|(i, pubkey)| BalanceChange {
+            pubkey: *pubkey,
+            before: before
+                .get(i)
+                .and_then(Option::as_ref)
+                .map_or(0, |account| account.lamports),
+            after: after
+                .get(i)
+                .and_then(Option::as_ref)
+                .map_or(0, |account| account.lamports),
+        })
+        .collect();
+
+    Ok(Outcome::Simulated(Simulation {
+        units_consumed: result.units_consumed,
+        logs,
+        balance_changes,
+        fee: client.get_fee_for_message(&transaction.message)?,
+    }))
+}
+
+/// Faucets answer over-eager clients with HTTP 429 or an airdrop limit message.
+fn is_rate_limited(e: &ClientError) -> bool {
+    let message = e.to_string().to_lowercase();
+
+    [
+        "429",
+        "too many requests",
+        "rate limit",
+        "airdrop limit",
+        "request limit",
+    ]
+    .iter()
+    .any(|needle| message.contains(needle))
+}
+
+/// Polls until `signature` reaches the client's commitment.
+fn wait_for_confirmation(client: &RpcClient, signature: &Signature) -> Result<(), ToolkitError> {
+    let started = Instant::now();
+
+    loop {
+        match client.get_signature_status_with_commitment(signature, client.commitment())? {
+            Some(Ok(())) => return Ok(()),
+            Some(Err(e)) => {
+                return Err(ToolkitError::Rpc(format!(
+                    "Transaction {} failed: {}",
+                    signature, e
+                )))
+            }
+            None if started.elapsed() >= CONFIRM_TIMEOUT => {
+                return Err(ToolkitError::Rpc(format!(
+                    "Transaction {} was not confirmed within {}s",
+                    signature,
+                    CONFIRM_TIMEOUT.as_secs()
+                )))
+            }
+            None => thread::sleep(CONFIRM_POLL_INTERVAL),
+        }
+    }
+}
+
+/// Sends `amount`, with the fees paid by `fee_payer` when given, else the sender.
+/// Nothing is signed until `confirm` accepts the summary, the fee includes the priority fee.
+pub fn transfer_sol(
+    sender: Wallet,
+    receiver: &Pubkey,
+    amount: Amount,
+    fee_payer: Option<Wallet>,
+    client: &RpcClient,
+    options: &SendOptions,
+    confirm: impl FnOnce(&TransferSummary) -> Result<(), ToolkitError>,
+) -> Result<(Outcome, TransferSummary), ToolkitError> {
+    let sender_keypair = sender.keypair()?;
+    let fee_payer = fee_payer.map(|wallet| wallet.keypair()).transpose()?;
+    let payer = fee_payer.as_ref().unwrap_or(&sender_keypair);
+
+    let balance = client.get_balance(&sender_keypair.pubkey())?;
 
     // Get recent blockhash
-    let recent_blockhash = client
-        .get_latest_blockhash()
-        .expect("Failed to get recent blockhash");
+    let recent_blockhash = client.get_latest_blockhash()?;
+
+    let budget = options.compute_budget.instructions(
+        client,
+        &payer.pubkey(),
+        &[transfer(&sender_keypair.pubkey(), receiver, balance)],
+    )?;
+    let instructions = |lamports: u64| {
+        [
+            budget.as_slice(),
+            &[transfer(&sender_keypair.pubkey(), receiver, lamports)],
+        ]
+        .concat()
+    };
 
-    // Create a test transaction to calculate fees
+    // The fee does not depend on the amount, so it is known before the amount is
     let message = Message::new_with_blockhash(
-        &[transfer(&keypair.pubkey(), &to_pubkey, balance)],
-        Some(&keypair.pubkey()),
+        &instructions(balance),
+        Some(&payer.pubkey()),
         &recent_blockhash,
     );
+    let fee = client.get_fee_for_message(&message)?;
+    let deducted = if payer.pubkey() == sender_keypair.pubkey() {
+        fee
+    } else {
+        0
+    };
 
-    // Calculate exact fee rate to transfer entire SOL amount out of account minus fees
-    let fee = client
-        .get_fee_for_message(&message)
-        .expect("Failed to get fee calculator");
-    // Print our transaction out
-    println!("Expected Fee: {} !", fee);
+    let amount = match amount {
+        Amount::Lamports(lamports) => lamports,
+        Amount::All => balance.saturating_sub(deducted),
+    };
+    let needed = amount.saturating_add(deducted);
+    if amount == 0 || needed > balance {
+        return Err(ToolkitError::InsufficientFunds {
+            needed: needed.max(deducted + 1),
+            available: balance,
+        });
+    }
+
+    let summary = TransferSummary {
+        from: sender_keypair.pubkey(),
+        to: *receiver,
+        amount,
+        fee,
+        fee_payer: payer.pubkey(),
+    };
+    confirm(&summary)?;
 
-    // Deduct fee from lamports amount and create a TX with correct balance
     let transaction = Transaction::new_signed_with_payer(
-        &[transfer(&keypair.pubkey(), &to_pubkey, balance - fee)],
-        Some(&keypair.pubkey()),
-        &vec![&keypair],
+        &instructions(amount),
+        Some(&payer.pubkey()),
+        &signers(&sender_keypair, payer),
         recent_blockhash,
     );
 
     // Send the transaction
-    let signature = client
-        .send_and_confirm_transaction(&transaction)
-        .expect("Failed to send transaction");
-
-    // Print our transaction out
-    println!(
-        "Success! Check out your TX here: https://explorer.solana.com/tx/{}/?cluster=devnet",
-        signature
-    );
+    let outcome = submit(client, &transaction, options.simulate)?;
+
+    Ok((outcome, summary))
 }
 
-/*
-#[test]
-    fn submit() {
-        const RPC_URL: &str = "https://api.devnet.solana.com";
+/// Lamports a sweep sends: the balance and the rent of the closed token accounts. A wallet
+/// paying its own fees also pays for them, and for the token accounts created for the
+/// recipient, out of that.
+fn sweep_amount(
+    balance: u64,
+    closed_rent: u64,
+    fee: u64,
+    created_rent: u64,
+    self_paid: bool,
+) -> Result<u64, ToolkitError> {
+    let available = balance.saturating_add(closed_rent);
+    let deducted = if self_paid {
+        fee.saturating_add(created_rent)
+    } else {
+        0
+    };
+    if available <= deducted {
+        return Err(ToolkitError::InsufficientFunds {
+            needed: deducted + 1,
+            available,
+        });
+    }
+
+    Ok(available - deducted)
+}
 
-        // Connected to Solana Devnet RPC Client
-        let client = RpcClient::new(RPC_URL);
+/// Empties `from` into `to_pubkey`. Its token accounts are closed first, their rent going
+/// back to the wallet and their tokens to `to_pubkey` when `move_tokens` is set. Then every
+/// lamport left is sent, which deletes the account. Nothing is signed until `confirm`
+/// accepts the plan. Every fee, priority fees included, is accounted for so exactly zero
+/// lamports are left.
+///
+/// With `options.simulate` each transaction is only simulated. The state does not change in
+/// between, so the final transfer is simulated with the balance the wallet has now, before
+/// the rent of its token accounts comes back.
+pub fn clean_wallet(
+    from: Wallet,
+    to_pubkey: &Pubkey,
+    move_tokens: bool,
+    fee_payer: Option<Wallet>,
+    client: &RpcClient,
+    options: &SendOptions,
+    confirm: impl FnOnce(&SweepPlan) -> Result<(), ToolkitError>,
+) -> Result<Sweep, ToolkitError> {
+    // Deserialize the JSON to a Wallet struct
+    let keypair = from.keypair()?;
+    let fee_payer = fee_payer.map(|wallet| wallet.keypair()).transpose()?;
+    let payer = fee_payer.as_ref().unwrap_or(&keypair);
+    let owner = keypair.pubkey();
 
-        // Open the JSON file
-        let file = File::open("wba-wallet.json").expect("Could not open file");
-        let reader = BufReader::new(file);
+    // Let's check the balance
+    let balance = client.get_balance(&owner)?;
+    let token_accounts = token::token_accounts(client, &owner)?;
+    verbose!(
+        "Left {} lamports and {} token accounts!",
+        balance,
+        token_accounts.len()
+    );
 
-        // Deserialize the JSON to a Wallet struct
-        let wallet: Wallet = from_reader(reader).expect("Could not read JSON");
+    if let Some(reason) = token_accounts
+        .iter()
+        .find_map(|token| token.close_blocker(&owner, move_tokens))
+    {
+        return Err(ToolkitError::InvalidInput(format!(
+            "Cannot sweep {}: {}",
+            owner, reason
+        )));
+    }
+
+    let mut batches = Vec::new();
+    for chunk in token_accounts.chunks(TOKEN_ACCOUNTS_PER_TX) {
+        let mut instructions = Vec::new();
+        for token in chunk {
+            instructions.extend(token::close_instructions(
+                client,
+                token,
+                &owner,
+                to_pubkey,
+                &payer.pubkey(),
+            )?);
+        }
+        let budget = options
+            .compute_budget
+            .instructions(client, &payer.pubkey(), &instructions)?;
+        batches.push([budget, instructions].concat());
+    }
+    let sweep_budget = options.compute_budget.instructions(
+        client,
+        &payer.pubkey(),
+        &[transfer(&owner, to_pubkey, balance)],
+    )?;
+    let sweep_instructions = |lamports: u64| {
+        [
+            sweep_budget.as_slice(),
+            &[transfer(&owner, to_pubkey, lamports)],
+        ]
+        .concat()
+    };
 
-        let signer = Keypair::from_bytes(&wallet.secret_key).expect("Invalid keypair");
+    // Get recent blockhash
+    let recent_blockhash = client.get_latest_blockhash()?;
+    let fee_for = |instructions: &[Instruction]| -> Result<u64, ToolkitError> {
+        let message =
+            Message::new_with_blockhash(instructions, Some(&payer.pubkey()), &recent_blockhash);
+        Ok(client.get_fee_for_message(&message)?)
+    };
 
-        let prereq = WbaPrereqProgram::derive_program_address(&[
-            b"prereq",
-            signer.pubkey().to_bytes().as_ref()
-        ]);
+    // The fee does not depend on the amount, so the final transfer is priced up front
+    let sweep_fee = fee_for(&sweep_instructions(balance))?;
+    let mut fee = sweep_fee;
+    for batch in &batches {
+        fee += fee_for(batch)?;
+    }
+    verbose!("Expected Fee: {} !", fee);
+
+    let closed_rent = token_accounts
+        .iter()
+        .map(|token| token.lamports)
+        .sum::<u64>();
+    let created_rent = token::created_account_rent(client, &token_accounts, to_pubkey)?;
+    let amount = sweep_amount(
+        balance,
+        closed_rent,
+        fee,
+        created_rent,
+        payer.pubkey() == owner,
+    )?;
+
+    confirm(&SweepPlan {
+        from: owner,
+        to: *to_pubkey,
+        balance,
+        token_accounts: token_accounts.clone(),
+        amount,
+        fee,
+        fee_payer: payer.pubkey(),
+    })?;
+
+    let mut token_outcomes = Vec::new();
+    for batch in &batches {
+        let transaction = Transaction::new_signed_with_payer(
+            batch,
+            Some(&payer.pubkey()),
+            &signers(&keypair, payer),
+            client.get_latest_blockhash()?,
+        );
+        token_outcomes.push(submit(client, &transaction, options.simulate)?);
+    }
+
+    // Closed token accounts and created ones changed the balance, sweep what is there now
+    let balance = client.get_balance(&owner)?;
+    let deducted = if payer.pubkey() == owner {
+        sweep_fee
+    } else {
+        0
+    };
+    if balance <= deducted {
+        return Err(ToolkitError::InsufficientFunds {
+            needed: deducted + 1,
+            available: balance,
+        });
+    }
 
-        // Define our instruction data
-        let args = CompleteArgs {
-            github: b"kox".to_vec()
-        };
+    let transaction = Transaction::new_signed_with_payer(
+        &sweep_instructions(balance - deducted),
+        Some(&payer.pubkey()),
+        &signers(&keypair, payer),
+        client.get_latest_blockhash()?,
+    );
 
-        // Get recent blockhash
-        let blockhash = client
-            .get_latest_blockhash()
-            .expect("Failed to get recent blockhash");
-
-        let transaction = WbaPrereqProgram::complete(
-            &[&signer.pubkey(), &prereq, &system_program::id()],
-            &args,
-            Some(&signer.pubkey()),
-            &[&signer],
-            blockhash
-        );
+    // Send the transaction
+    let outcome = submit(client, &transaction, options.simulate)?;
+
+    // An account left with zero lamports is deleted by the runtime
+    if let Outcome::Sent(signature) = &outcome {
+        if client
+            .get_account_with_commitment(&owner, client.commitment())?
+            .value
+            .is_some()
+        {
+            return Err(ToolkitError::Rpc(format!(
+                "Account {} still exists after the sweep in {}",
+                owner, signature
+            )));
+        }
+    }
+
+    Ok(Sweep {
+        outcome,
+        balance,
+        fee,
+        amount: balance - deducted,
+        token_accounts,
+        token_outcomes,
+    })
+}
 
-        // Send the transaction
-        let signature = client
-            .send_and_confirm_transaction(&transaction)
-            .expect("Failed to send transaction");
+/// The owner signs, and so does the fee payer when it is a different wallet.
+pub fn signers<'a>(owner: &'a Keypair, payer: &'a Keypair) -> Vec<&'a Keypair> {
+    if owner.pubkey() == payer.pubkey() {
+        vec![owner]
+    } else {
+        vec![owner, payer]
+    }
+}
 
-        // Print our transaction out
-        println!("Success! Check out your TX here: https://explorer.solana.com/tx/{}/?cluster=devnet", signature);
-    } */
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sweep_amount() {
+        // 0.01 SOL plus two closed token accounts, 10000 lamports of fees
+        assert_eq!(
+            sweep_amount(10_000_000, 4_078_560, 10_000, 0, true).unwrap(),
+            14_068_560
+        );
+        // One recipient token account to create, paid by the wallet
+        assert_eq!(
+            sweep_amount(10_000_000, 4_078_560, 10_000, 2_039_280, true).unwrap(),
+            12_029_280
+        );
+        // Another fee payer covers fees and created accounts alike
+        assert_eq!(
+            sweep_amount(10_000_000, 4_078_560, 10_000, 2_039_280, false).unwrap(),
+            14_078_560
+        );
+
+        assert!(matches!(
+            sweep_amount(5_000, 0, 5_000, 0, true),
+            Err(ToolkitError::InsufficientFunds {
+                needed: 5_001,
+                available: 5_000
+            })
+        ));
+        assert!(sweep_amount(1_000_000, 0, 5_000, 2_039_280, true).is_err());
+    }
+}
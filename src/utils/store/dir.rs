@@ -0,0 +1,255 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{StoreBatch, StoreOp, WalletStore};
+use crate::utils::error::ToolkitError;
+
+const EXTENSION: &str = "json";
+const STAGED_EXTENSION: &str = "tmp";
+const JOURNAL: &str = "batch.journal";
+
+/// A flat directory with one JSON file per record, easy to inspect, diff and back up.
+///
+/// A batch is staged in temporary files and committed by renaming its journal into
+/// place. Only then are the records replaced, and a batch cut short by a crash is
+/// finished from the journal the next time the store is opened, so it is applied as a
+/// whole or not at all.
+pub struct DirStore {
+    dir: PathBuf,
+}
+
+/// One write of a committed batch. Both can be repeated, so a journal is simply
+/// replayed from the start.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "lowercase")]
+enum JournalOp {
+    /// Moves the staged file, when it is still there, into place as the record `key`
+    Put { key: String, staged: String },
+    Delete { key: String },
+}
+
+impl DirStore {
+    pub fn open(dir: &Path) -> Result<DirStore, ToolkitError> {
+        fs::create_dir_all(dir)?;
+
+        let store = DirStore {
+            dir: dir.to_path_buf(),
+        };
+        store.replay_journal()?;
+
+        // Whatever is still staged belongs to a batch that was never committed
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == STAGED_EXTENSION) {
+                fs::remove_file(path)?;
+            }
+        }
+
+        Ok(store)
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", encode_key(key), EXTENSION))
+    }
+
+    fn journal_path(&self) -> PathBuf {
+        self.dir.join(JOURNAL)
+    }
+
+    /// Finishes a committed batch that was not fully applied.
+    fn replay_journal(&self) -> Result<(), ToolkitError> {
+        let journal = self.journal_path();
+        if !journal.exists() {
+            return Ok(());
+        }
+
+        let ops: Vec<JournalOp> = serde_json::from_slice(&fs::read(&journal)?)?;
+        self.apply(&ops)
+    }
+
+    fn apply(&self, ops: &[JournalOp]) -> Result<(), ToolkitError> {
+        for op in ops {
+            match op {
+                JournalOp::Put { key, staged } => {
+                    let staged = self.dir.join(staged);
+                    // Gone when an earlier run already moved it into place
+                    if staged.exists() {
+                        fs::rename(staged, self.path(key))?;
+                    }
+                }
+                JournalOp::Delete { key } => {
+                    let path = self.path(key);
+                    if path.exists() {
+                        fs::remove_file(path)?;
+                    }
+                }
+            }
+        }
+        fs::remove_file(self.journal_path())?;
+
+        Ok(())
+    }
+
+    /// Writes the staged files and the journal of `ops`, pushing every file it creates
+    /// to `created` so a failure can remove them again.
+    fn stage(
+        &self,
+        ops: BTreeMap<String, Option<Vec<u8>>>,
+        created: &mut Vec<PathBuf>,
+    ) -> Result<Vec<JournalOp>, ToolkitError> {
+        let mut journal = Vec::new();
+
+        for (i, (key, value)) in ops.into_iter().enumerate() {
+            match value {
+                Some(value) => {
+                    let staged = format!("{}.{}.{}", encode_key(&key), i, STAGED_EXTENSION);
+                    let path = self.dir.join(&staged);
+                    created.push(path.clone());
+                    write_file(&path, &value)?;
+                    journal.push(JournalOp::Put { key, staged });
+                }
+                None => journal.push(JournalOp::Delete { key }),
+            }
+        }
+
+        let path = self
+            .journal_path()
+            .with_extension(format!("journal.{}", STAGED_EXTENSION));
+        created.push(path.clone());
+        write_file(&path, &serde_json::to_vec(&journal)?)?;
+
+        Ok(journal)
+    }
+}
+
+/// Turns a key into a portable file name: anything but ASCII letters, digits, `-`,
+/// `_` and `.` is percent-encoded, so `wallet:alice` becomes `wallet%3Aalice`.
+fn encode_key(key: &str) -> String {
+    key.bytes()
+        .map(|b| match b {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn decode_key(name: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(name.len());
+    let mut chars = name.bytes();
+
+    while let Some(b) = chars.next() {
+        if b == b'%' {
+            let hex = [chars.next()?, chars.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(b);
+        }
+    }
+
+    String::from_utf8(bytes).ok()
+}
+
+fn write_file(path: &Path, value: &[u8]) -> Result<(), ToolkitError> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(path)?;
+    file.write_all(value)?;
+    file.sync_all()?;
+
+    Ok(())
+}
+
+impl WalletStore for DirStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, ToolkitError> {
+        let path = self.path(key);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        Ok(Some(fs::read(path)?))
+    }
+
+    fn scan(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>, ToolkitError> {
+        let mut entries = Vec::new();
+
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != EXTENSION) {
+                continue;
+            }
+
+            // Files that don't decode to a key were not written by the toolkit
+            let key = match path
+                .file_stem()
+                .and_then(|stem| decode_key(&stem.to_string_lossy()))
+            {
+                Some(key) => key,
+                None => continue,
+            };
+            if key.starts_with(prefix) {
+                entries.push((key, fs::read(&path)?));
+            }
+        }
+        entries.sort();
+
+        Ok(entries)
+    }
+
+    fn write(&self, batch: StoreBatch) -> Result<(), ToolkitError> {
+        // A batch is only written once the one before it is complete
+        self.replay_journal()?;
+
+        // Applied in order, a batch leaves each key with its last write
+        let mut ops = BTreeMap::new();
+        for op in batch.into_ops() {
+            match op {
+                StoreOp::Put(key, value) => ops.insert(key, Some(value)),
+                StoreOp::Delete(key) => ops.insert(key, None),
+            };
+        }
+
+        // Stage every new value and the journal before touching a live record
+        let mut created = Vec::new();
+        let journal = match self.stage(ops, &mut created) {
+            Ok(journal) => journal,
+            Err(e) => {
+                for path in created {
+                    let _ = fs::remove_file(path);
+                }
+                return Err(e);
+            }
+        };
+
+        // Renaming the journal into place commits the batch
+        let staged_journal = created.pop().ok_or_else(|| {
+            ToolkitError::Storage("Staged batch journal went missing".to_string())
+        })?;
+        if let Err(e) = fs::rename(&staged_journal, self.journal_path()) {
+            let _ = fs::remove_file(staged_journal);
+            for path in created {
+                let _ = fs::remove_file(path);
+            }
+            return Err(e.into());
+        }
+
+        self.apply(&journal).map_err(|e| {
+            ToolkitError::Storage(format!(
+                "The batch was committed but not fully applied, it is finished the next time the store is opened: {}",
+                e
+            ))
+        })
+    }
+}
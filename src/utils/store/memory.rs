@@ -0,0 +1,54 @@
+use std::{collections::BTreeMap, sync::RwLock};
+
+use super::{StoreBatch, StoreOp, WalletStore};
+use crate::utils::error::ToolkitError;
+
+/// Keeps every record in memory, for tests and throwaway sessions.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    entries: RwLock<BTreeMap<String, Vec<u8>>>,
+}
+
+impl WalletStore for MemoryStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, ToolkitError> {
+        let entries = self
+            .entries
+            .read()
+            .map_err(|e| ToolkitError::Storage(e.to_string()))?;
+
+        Ok(entries.get(key).cloned())
+    }
+
+    fn scan(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>, ToolkitError> {
+        let entries = self
+            .entries
+            .read()
+            .map_err(|e| ToolkitError::Storage(e.to_string()))?;
+
+        Ok(entries
+            .range(prefix.to_string()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect())
+    }
+
+    fn write(&self, batch: StoreBatch) -> Result<(), ToolkitError> {
+        let mut entries = self
+            .entries
+            .write()
+            .map_err(|e| ToolkitError::Storage(e.to_string()))?;
+
+        for op in batch.into_ops() {
+            match op {
+                StoreOp::Put(key, value) => {
+                    entries.insert(key, value);
+                }
+                StoreOp::Delete(key) => {
+                    entries.remove(&key);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,277 @@
+use std::path::Path;
+
+use clap::ValueEnum;
+
+use super::error::ToolkitError;
+
+mod dir;
+mod memory;
+#[cfg(feature = "rocksdb")]
+mod rocks;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+pub use dir::DirStore;
+pub use memory::MemoryStore;
+#[cfg(feature = "rocksdb")]
+pub use rocks::RocksStore;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+
+/// A single write in a [`StoreBatch`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum StoreOp {
+    Put(String, Vec<u8>),
+    Delete(String),
+}
+
+/// Writes applied together by [`WalletStore::write`], like a RocksDB `WriteBatch`.
+#[derive(Debug, Default)]
+pub struct StoreBatch {
+    ops: Vec<StoreOp>,
+}
+
+impl StoreBatch {
+    pub fn put(&mut self, key: &str, value: impl AsRef<[u8]>) {
+        self.ops
+            .push(StoreOp::Put(key.to_string(), value.as_ref().to_vec()));
+    }
+
+    pub fn delete(&mut self, key: &str) {
+        self.ops.push(StoreOp::Delete(key.to_string()));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    pub fn into_ops(self) -> Vec<StoreOp> {
+        self.ops
+    }
+}
+
+/// Where wallet records live. Every backend is a sorted key-value store with
+/// atomic batches, which is all the wallet code needs from RocksDB.
+pub trait WalletStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, ToolkitError>;
+
+    /// Every entry whose key starts with `prefix`, sorted by key.
+    fn scan(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>, ToolkitError>;
+
+    /// Applies every write in `batch`, or none of them.
+    fn write(&self, batch: StoreBatch) -> Result<(), ToolkitError>;
+
+    fn put(&self, key: &str, value: &[u8]) -> Result<(), ToolkitError> {
+        let mut batch = StoreBatch::default();
+        batch.put(key, value);
+        self.write(batch)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), ToolkitError> {
+        let mut batch = StoreBatch::default();
+        batch.delete(key);
+        self.write(batch)
+    }
+}
+
+/// Lets a store picked at runtime be passed wherever a `&dyn WalletStore` is expected.
+impl<T: WalletStore + ?Sized> WalletStore for Box<T> {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, ToolkitError> {
+        (**self).get(key)
+    }
+
+    fn scan(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>, ToolkitError> {
+        (**self).scan(prefix)
+    }
+
+    fn write(&self, batch: StoreBatch) -> Result<(), ToolkitError> {
+        (**self).write(batch)
+    }
+}
+
+/// The storage backends `--store` can pick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StoreKind {
+    /// RocksDB database directory
+    Rocksdb,
+    /// Directory with one JSON file per record
+    Dir,
+    /// Single SQLite database file
+    Sqlite,
+    /// Kept in memory and gone when the command exits
+    Memory,
+}
+
+impl StoreKind {
+    fn scheme(self) -> &'static str {
+        match self {
+            StoreKind::Rocksdb => "rocksdb",
+            StoreKind::Dir => "dir",
+            StoreKind::Sqlite => "sqlite",
+            StoreKind::Memory => "memory",
+        }
+    }
+
+    /// Splits a `scheme:path` location like `sqlite:wallets.db` into its backend and path.
+    fn from_location(location: &str) -> Option<(StoreKind, &str)> {
+        let (scheme, path) = location.split_once(':')?;
+        let kind = StoreKind::value_variants()
+            .iter()
+            .find(|kind| kind.scheme() == scheme)?;
+
+        Some((*kind, path))
+    }
+}
+
+/// Opens the store at `location`. The backend is taken from a `scheme:` prefix on the
+/// location, then from `kind`, and defaults to RocksDB.
+pub fn open_store(
+    location: &str,
+    kind: Option<StoreKind>,
+) -> Result<Box<dyn WalletStore>, ToolkitError> {
+    let (kind, path) = match (StoreKind::from_location(location), kind) {
+        (Some((scheme, _)), Some(kind)) if scheme != kind => {
+            return Err(ToolkitError::InvalidInput(format!(
+                "{} is a {} location but --store {} was given",
+                location,
+                scheme.scheme(),
+                kind.scheme()
+            )));
+        }
+        (Some((scheme, path)), _) => (scheme, path),
+        (None, kind) => (kind.unwrap_or(StoreKind::Rocksdb), location),
+    };
+    let path = Path::new(path);
+
+    match kind {
+        #[cfg(feature = "rocksdb")]
+        StoreKind::Rocksdb => Ok(Box::new(RocksStore::open(path)?)),
+        StoreKind::Dir => Ok(Box::new(DirStore::open(path)?)),
+        #[cfg(feature = "sqlite")]
+        StoreKind::Sqlite => Ok(Box::new(SqliteStore::open(path)?)),
+        StoreKind::Memory => Ok(Box::new(MemoryStore::default())),
+        #[allow(unreachable_patterns)]
+        kind => Err(ToolkitError::InvalidInput(format!(
+            "This toolkit was built without the {} store, rebuild with `--features {}`",
+            kind.scheme(),
+            kind.scheme()
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    /// Behaviour every backend has to share.
+    fn exercise_store(store: &dyn WalletStore) {
+        assert_eq!(store.get("wallet:alice").unwrap(), None);
+
+        store.put("wallet:bob", b"2").unwrap();
+        store.put("wallet:alice", b"1").unwrap();
+        store.put("seed:carol", b"3").unwrap();
+        store.put("trash:wallet:dave", b"4").unwrap();
+        assert_eq!(store.get("wallet:alice").unwrap(), Some(b"1".to_vec()));
+
+        let keys: Vec<_> = store
+            .scan("wallet:")
+            .unwrap()
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+        assert_eq!(keys, vec!["wallet:alice", "wallet:bob"]);
+        assert_eq!(store.scan("").unwrap().len(), 4);
+
+        let mut batch = StoreBatch::default();
+        batch.put("wallet:erin", b"5");
+        batch.delete("wallet:bob");
+        store.write(batch).unwrap();
+        assert_eq!(store.get("wallet:bob").unwrap(), None);
+        assert_eq!(store.get("wallet:erin").unwrap(), Some(b"5".to_vec()));
+
+        // Names are free-form, keys must survive whatever a backend does to store them
+        store.put("wallet:a/b c:%", b"6").unwrap();
+        assert_eq!(store.get("wallet:a/b c:%").unwrap(), Some(b"6".to_vec()));
+        assert!(store
+            .scan("wallet:a")
+            .unwrap()
+            .iter()
+            .any(|(key, _)| key == "wallet:a/b c:%"));
+
+        store.delete("wallet:alice").unwrap();
+        assert_eq!(store.get("wallet:alice").unwrap(), None);
+    }
+
+    #[test]
+    fn test_memory_store() {
+        exercise_store(&MemoryStore::default());
+    }
+
+    #[test]
+    fn test_dir_store() {
+        let tmp_dir = TempDir::new("dir_store").unwrap();
+        exercise_store(&DirStore::open(&tmp_dir.path().join("wallets")).unwrap());
+    }
+
+    #[test]
+    fn test_dir_store_finishes_or_drops_cut_short_batches() {
+        let tmp_dir = TempDir::new("dir_store").unwrap();
+        let path = tmp_dir.path().join("wallets");
+        DirStore::open(&path).unwrap().put("wallet:bob", b"2").unwrap();
+
+        // A committed batch the process died applying, half of it already done
+        std::fs::write(path.join("wallet%3Aerin.0.tmp"), b"5").unwrap();
+        std::fs::write(path.join("wallet%3Afrank.json"), b"6").unwrap();
+        std::fs::write(
+            path.join("batch.journal"),
+            r#"[{"put":{"key":"wallet:erin","staged":"wallet%3Aerin.0.tmp"}},
+                {"put":{"key":"wallet:frank","staged":"wallet%3Afrank.1.tmp"}},
+                {"delete":{"key":"wallet:bob"}}]"#,
+        )
+        .unwrap();
+        // And the staged file of a batch that was never committed
+        std::fs::write(path.join("wallet%3Azed.0.tmp"), b"7").unwrap();
+
+        let store = DirStore::open(&path).unwrap();
+        let keys: Vec<_> = store
+            .scan("")
+            .unwrap()
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+        assert_eq!(keys, vec!["wallet:erin", "wallet:frank"]);
+        assert_eq!(std::fs::read_dir(&path).unwrap().count(), 2);
+    }
+
+    #[cfg(feature = "rocksdb")]
+    #[test]
+    fn test_rocks_store() {
+        let tmp_dir = TempDir::new("test_db").unwrap();
+        exercise_store(&RocksStore::open(tmp_dir.path()).unwrap());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_store() {
+        let tmp_dir = TempDir::new("sqlite_store").unwrap();
+        exercise_store(&SqliteStore::open(&tmp_dir.path().join("wallets.db")).unwrap());
+    }
+
+    #[test]
+    fn test_store_location() {
+        assert_eq!(
+            StoreKind::from_location("sqlite:wallets.db"),
+            Some((StoreKind::Sqlite, "wallets.db"))
+        );
+        assert_eq!(
+            StoreKind::from_location("dir:/tmp/wallets"),
+            Some((StoreKind::Dir, "/tmp/wallets"))
+        );
+        assert_eq!(StoreKind::from_location("wba_toolkit"), None);
+        assert_eq!(StoreKind::from_location("C:\\wallets"), None);
+
+        assert!(open_store("memory:", None).is_ok());
+        assert!(open_store("memory:", Some(StoreKind::Dir)).is_err());
+    }
+}
@@ -0,0 +1,60 @@
+use std::{fs, path::Path};
+
+use rocksdb::{WriteBatch, DB};
+
+use super::{StoreBatch, StoreOp, WalletStore};
+use crate::utils::error::ToolkitError;
+
+/// A RocksDB database directory, the toolkit's original store.
+pub struct RocksStore {
+    db: DB,
+}
+
+impl RocksStore {
+    /// Opens the database at `path`, creating the directory if it doesn't exist.
+    pub fn open(path: &Path) -> Result<RocksStore, ToolkitError> {
+        if !path.exists() {
+            fs::create_dir(path)?;
+        }
+
+        Ok(RocksStore {
+            db: DB::open_default(path)?,
+        })
+    }
+}
+
+impl WalletStore for RocksStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, ToolkitError> {
+        Ok(self.db.get(key)?)
+    }
+
+    fn scan(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>, ToolkitError> {
+        let mut entries = Vec::new();
+
+        for item in self.db.prefix_iterator(prefix.as_bytes()) {
+            let (key, value) = item?;
+            // The iterator runs past the prefix without a prefix extractor
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+
+            // Keys are always written as strings, anything else is reported by `db check`
+            entries.push((String::from_utf8_lossy(&key).to_string(), value.to_vec()));
+        }
+
+        Ok(entries)
+    }
+
+    fn write(&self, batch: StoreBatch) -> Result<(), ToolkitError> {
+        let mut write_batch = WriteBatch::default();
+
+        for op in batch.into_ops() {
+            match op {
+                StoreOp::Put(key, value) => write_batch.put(key, value),
+                StoreOp::Delete(key) => write_batch.delete(key),
+            }
+        }
+
+        Ok(self.db.write(write_batch)?)
+    }
+}
@@ -0,0 +1,66 @@
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::{StoreBatch, StoreOp, WalletStore};
+use crate::utils::error::ToolkitError;
+
+/// A single SQLite database file holding every record in one table.
+pub struct SqliteStore {
+    conn: Connection,
+}
+
+impl SqliteStore {
+    pub fn open(path: &Path) -> Result<SqliteStore, ToolkitError> {
+        let conn = Connection::open(path)?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS entries (key TEXT PRIMARY KEY NOT NULL, value BLOB NOT NULL)",
+            [],
+        )?;
+
+        Ok(SqliteStore { conn })
+    }
+}
+
+impl WalletStore for SqliteStore {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, ToolkitError> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT value FROM entries WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    fn scan(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>, ToolkitError> {
+        // Text keys sort bytewise, the same order as the other backends
+        let mut statement = self
+            .conn
+            .prepare("SELECT key, value FROM entries WHERE substr(key, 1, ?2) = ?1 ORDER BY key")?;
+        let rows = statement.query_map(params![prefix, prefix.chars().count()], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    fn write(&self, batch: StoreBatch) -> Result<(), ToolkitError> {
+        let transaction = self.conn.unchecked_transaction()?;
+
+        for op in batch.into_ops() {
+            match op {
+                StoreOp::Put(key, value) => transaction.execute(
+                    "INSERT OR REPLACE INTO entries (key, value) VALUES (?1, ?2)",
+                    params![key, value],
+                )?,
+                StoreOp::Delete(key) => {
+                    transaction.execute("DELETE FROM entries WHERE key = ?1", params![key])?
+                }
+            };
+        }
+
+        Ok(transaction.commit()?)
+    }
+}
@@ -0,0 +1,279 @@
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{
+    instruction::Instruction, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent,
+};
+use spl_token_2022::{
+    extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+    instruction::{close_account, transfer_checked},
+    state::{Account, AccountState, Mint},
+};
+
+use super::error::ToolkitError;
+use super::solana::MAX_MULTIPLE_ACCOUNTS;
+
+/// Token programs whose accounts a wallet can own. Token-2022 accounts share the
+/// layout of the original program, followed by their extensions.
+const TOKEN_PROGRAMS: [Pubkey; 2] = [spl_token::ID, spl_token_2022::ID];
+
+/// Offset of the owner in a token account, right after the mint.
+const OWNER_OFFSET: usize = 32;
+
+/// A token account owned by a wallet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenAccount {
+    pub address: Pubkey,
+    /// Token program the account belongs to
+    pub program: Pubkey,
+    pub mint: Pubkey,
+    /// Token balance in base units
+    pub amount: u64,
+    /// Lamports held, returned to the owner on close
+    pub lamports: u64,
+    /// Wrapped SOL, whose tokens are the lamports themselves
+    pub is_native: bool,
+    pub frozen: bool,
+    pub close_authority: Option<Pubkey>,
+}
+
+impl TokenAccount {
+    /// Why the account cannot be closed by its owner, if anything stands in the way.
+    pub fn close_blocker(&self, owner: &Pubkey, move_tokens: bool) -> Option<String> {
+        if self.frozen {
+            return Some(format!("token account {} is frozen", self.address));
+        }
+        if self
+            .close_authority
+            .is_some_and(|authority| authority != *owner)
+        {
+            return Some(format!(
+                "token account {} can only be closed by its close authority",
+                self.address
+            ));
+        }
+        if self.amount > 0 && !self.is_native && !move_tokens {
+            return Some(format!(
+                "token account {} still holds {} tokens of mint {}, pass --move-tokens to send them along",
+                self.address, self.amount, self.mint
+            ));
+        }
+
+        None
+    }
+}
+
+/// Every token account owned by `owner`, in both token programs.
+pub fn token_accounts(
+    client: &RpcClient,
+    owner: &Pubkey,
+) -> Result<Vec<TokenAccount>, ToolkitError> {
+    let mut accounts = Vec::new();
+
+    for program in TOKEN_PROGRAMS {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                OWNER_OFFSET,
+                owner.as_ref(),
+            ))]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+
+        for (address, account) in client.get_program_accounts_with_config(&program, config)? {
+            // Mints can match the owner filter by chance, they do not unpack as accounts
+            let Ok(state) = StateWithExtensions::<Account>::unpack(&account.data) else {
+                continue;
+            };
+            if state.base.owner != *owner {
+                continue;
+            }
+
+            accounts.push(TokenAccount {
+                address,
+                program,
+                mint: state.base.mint,
+                amount: state.base.amount,
+                lamports: account.lamports,
+                is_native: state.base.is_native(),
+                frozen: state.base.state == AccountState::Frozen,
+                close_authority: state.base.close_authority.into(),
+            });
+        }
+    }
+
+    Ok(accounts)
+}
+
+fn mint_decimals(client: &RpcClient, token: &TokenAccount) -> Result<u8, ToolkitError> {
+    let mint = client.get_account(&token.mint)?;
+
+    StateWithExtensions::<Mint>::unpack(&mint.data)
+        .map(|mint| mint.base.decimals)
+        .map_err(|e| ToolkitError::Decode(format!("Invalid mint {}: {}", token.mint, e)))
+}
+
+/// Size of an associated token account for the mint of `token`. Token-2022 accounts carry
+/// the extensions their mint requires, and the ATA program always adds `ImmutableOwner`.
+fn associated_account_len(client: &RpcClient, token: &TokenAccount) -> Result<usize, ToolkitError> {
+    if token.program != spl_token_2022::ID {
+        return Ok(spl_token::state::Account::LEN);
+    }
+
+    let invalid_mint =
+        |e: ProgramError| ToolkitError::Decode(format!("Invalid mint {}: {}", token.mint, e));
+    let mint = client.get_account(&token.mint)?;
+    let mint = StateWithExtensions::<Mint>::unpack(&mint.data).map_err(invalid_mint)?;
+    let mut extensions = ExtensionType::get_required_init_account_extensions(
+        &mint.get_extension_types().map_err(invalid_mint)?,
+    );
+    if !extensions.contains(&ExtensionType::ImmutableOwner) {
+        extensions.push(ExtensionType::ImmutableOwner);
+    }
+
+    ExtensionType::try_calculate_account_len::<Account>(&extensions).map_err(invalid_mint)
+}
+
+/// Rent of the associated token accounts of `recipient` that closing `tokens` creates:
+/// those receiving tokens that do not exist yet.
+pub fn created_account_rent(
+    client: &RpcClient,
+    tokens: &[TokenAccount],
+    recipient: &Pubkey,
+) -> Result<u64, ToolkitError> {
+    let mut destinations: Vec<(Pubkey, &TokenAccount)> = Vec::new();
+    for token in tokens
+        .iter()
+        .filter(|token| token.amount > 0 && !token.is_native)
+    {
+        let destination =
+            get_associated_token_address_with_program_id(recipient, &token.mint, &token.program);
+        if !destinations
+            .iter()
+            .any(|(address, _)| *address == destination)
+        {
+            destinations.push((destination, token));
+        }
+    }
+
+    let mut rent = 0;
+    for batch in destinations.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let addresses: Vec<Pubkey> = batch.iter().map(|(address, _)| *address).collect();
+        let accounts = client.get_multiple_accounts(&addresses)?;
+
+        for ((_, token), account) in batch.iter().zip(accounts) {
+            if account.is_none() {
+                let len = associated_account_len(client, token)?;
+                rent += client.get_minimum_balance_for_rent_exemption(len)?;
+            }
+        }
+    }
+
+    Ok(rent)
+}
+
+/// Closes `token` with its rent going back to `owner`. Tokens still in it are first moved
+/// to the associated token account of `recipient`, created by `payer` when missing.
+pub fn close_instructions(
+    client: &RpcClient,
+    token: &TokenAccount,
+    owner: &Pubkey,
+    recipient: &Pubkey,
+    payer: &Pubkey,
+) -> Result<Vec<Instruction>, ToolkitError> {
+    let instruction_error = |e: ProgramError| {
+        ToolkitError::InvalidInput(format!("Cannot close {}: {}", token.address, e))
+    };
+    let mut instructions = Vec::new();
+
+    if token.amount > 0 && !token.is_native {
+        let decimals = mint_decimals(client, token)?;
+        let destination =
+            get_associated_token_address_with_program_id(recipient, &token.mint, &token.program);
+
+        instructions.push(create_associated_token_account_idempotent(
+            payer,
+            recipient,
+            &token.mint,
+            &token.program,
+        ));
+        instructions.push(
+            transfer_checked(
+                &token.program,
+                &token.address,
+                &token.mint,
+                &destination,
+                owner,
+                &[],
+                token.amount,
+                decimals,
+            )
+            .map_err(instruction_error)?,
+        );
+    }
+    instructions.push(
+        close_account(&token.program, &token.address, owner, owner, &[])
+            .map_err(instruction_error)?,
+    );
+
+    Ok(instructions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_close_blocker() {
+        let owner = Pubkey::new_unique();
+        let token = TokenAccount {
+            address: Pubkey::new_unique(),
+            program: spl_token::ID,
+            mint: Pubkey::new_unique(),
+            amount: 0,
+            lamports: 2_039_280,
+            is_native: false,
+            frozen: false,
+            close_authority: None,
+        };
+        assert_eq!(token.close_blocker(&owner, false), None);
+
+        let holding = TokenAccount {
+            amount: 5,
+            ..token.clone()
+        };
+        assert!(holding
+            .close_blocker(&owner, false)
+            .unwrap()
+            .contains("--move-tokens"));
+        assert_eq!(holding.close_blocker(&owner, true), None);
+
+        let wrapped_sol = TokenAccount {
+            is_native: true,
+            ..holding.clone()
+        };
+        assert_eq!(wrapped_sol.close_blocker(&owner, false), None);
+
+        let frozen = TokenAccount {
+            frozen: true,
+            ..token.clone()
+        };
+        assert!(frozen.close_blocker(&owner, true).is_some());
+
+        let delegated = TokenAccount {
+            close_authority: Some(Pubkey::new_unique()),
+            ..token
+        };
+        assert!(delegated.close_blocker(&owner, true).is_some());
+    }
+}
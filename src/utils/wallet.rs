@@ -1,94 +1,878 @@
-use std::{collections::HashMap, error::Error};
+use std::{collections::BTreeMap, fmt};
 
-use rocksdb::DB;
+use bip39::Mnemonic;
+use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 use solana_sdk::{signature::Keypair, signer::Signer};
 
+use super::address::{
+    get_watch_key, list_watch_wallets, read_watch_meta, update_watch_meta, watch_wallet_exists,
+};
+use super::crypto::{
+    decrypt_secret, encrypt_secret, EncryptedSecret, PassphraseSource, SecretBytes,
+};
+use super::error::ToolkitError;
+use super::meta::{now, Origin, SortKey, WalletFilter, WalletMeta};
+use super::mnemonic::{generate_mnemonic, keypair_from_mnemonic, parse_mnemonic};
+use super::output::verbose;
+use super::seed::{
+    get_seed_key, list_child_wallets, list_seed_wallets, parse_child_name, read_child_meta,
+    read_child_wallet, read_seed_meta, seed_record_secret, update_child_meta, update_seed_meta,
+};
+use super::store::{StoreBatch, WalletStore};
 
+/// A wallet with its secret. Printing it, with `Display` or `Debug`, never shows the secret.
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct Wallet {
     pub pubkey: String,
-    pub secret_key: Vec<u8>,
+    pub secret_key: SecretBytes,
+}
+
+impl fmt::Display for Wallet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.pubkey)
+    }
+}
+
+impl Wallet {
+    /// The signing keypair behind this wallet.
+    pub fn keypair(&self) -> Result<Keypair, ToolkitError> {
+        Keypair::from_bytes(&self.secret_key)
+            .map_err(|e| ToolkitError::Decode(format!("Invalid secret key: {}", e)))
+    }
+}
+
+/// Version of the stored wallet record.
+/// 1: pubkey and encrypted secret, 2: adds wallet metadata.
+pub const WALLET_RECORD_VERSION: u32 = 2;
+
+fn legacy_record_version() -> u32 {
+    1
+}
+
+/// What actually lands in the wallet store: the pubkey and metadata in clear, the secret sealed.
+#[derive(Serialize, Deserialize, Debug)]
+struct StoredWallet {
+    #[serde(default = "legacy_record_version")]
+    version: u32,
+    pubkey: String,
+    secret: EncryptedSecret,
+    #[serde(default)]
+    meta: WalletMeta,
+}
+
+impl StoredWallet {
+    fn seal(
+        wallet: &Wallet,
+        meta: WalletMeta,
+        passphrase: &str,
+    ) -> Result<StoredWallet, ToolkitError> {
+        Ok(StoredWallet {
+            version: WALLET_RECORD_VERSION,
+            pubkey: wallet.pubkey.clone(),
+            secret: encrypt_secret(&wallet.secret_key, passphrase)?,
+            meta,
+        })
+    }
+
+    fn open(self, passphrase: &str) -> Result<Wallet, ToolkitError> {
+        Ok(Wallet {
+            pubkey: self.pubkey,
+            secret_key: decrypt_secret(&self.secret, passphrase)?,
+        })
+    }
 }
 
 pub fn get_wallet_key(name: &str) -> String {
     format!("wallet:{}", name)
 }
 
-pub fn save_wallet_to_db(wallet: &Wallet, db: &DB, wallet_name: &str) -> Result<(), Box<dyn Error>> {
-    // Serialize the Wallet struct
-    let wallet_value = serde_json::to_vec(wallet)?;
+/// Removed records are kept under `trash:` plus their original key and the time they
+/// were removed, so removing a name again never replaces the earlier one, until purged.
+pub fn get_trash_key(record_key: &str, removed_at: u64) -> String {
+    format!("trash:{}:{}", record_key, removed_at)
+}
+
+/// Splits a trash key into the original record key and the time it was removed, 0 for
+/// entries trashed before removals were timestamped.
+fn parse_trash_key(key: &str) -> Option<(String, u64)> {
+    let (kind, rest) = key.strip_prefix("trash:")?.split_once(':')?;
+
+    match rest.rsplit_once(':') {
+        Some((name, removed_at)) if removed_at.parse::<u64>().is_ok() => Some((
+            format!("{}:{}", kind, name),
+            removed_at.parse().ok()?,
+        )),
+        _ => Some((format!("{}:{}", kind, rest), 0)),
+    }
+}
+
+/// Every record key a wallet called `name` can live under: plain, seed or watch-only.
+fn record_keys(name: &str) -> [String; 3] {
+    [
+        get_wallet_key(name),
+        get_seed_key(name),
+        get_watch_key(name),
+    ]
+}
+
+/// Puts `value` under `record_key`, the record of the wallet `name`, and deletes the
+/// records of the other kinds under that name. A forced overwrite thus never leaves a
+/// plain and a seed wallet sharing one name.
+pub fn replace_wallet_record(batch: &mut StoreBatch, name: &str, record_key: &str, value: &[u8]) {
+    for key in record_keys(name) {
+        if key != record_key {
+            batch.delete(&key);
+        }
+    }
+    batch.put(record_key, value);
+}
+
+/// Whether `name` is already used by a plain, seed or watch-only wallet.
+pub fn wallet_exists(db: &dyn WalletStore, name: &str) -> Result<bool, ToolkitError> {
+    for key in record_keys(name) {
+        if db.get(&key)?.is_some() {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Refuses names that could never be looked up again. A `/` belongs to seed wallet
+/// children, which a name containing one would shadow, and a `:` to the `wallet:`,
+/// `watch:` and `contact:` prefixes an address is read with first.
+pub fn check_name(what: &str, name: &str) -> Result<(), ToolkitError> {
+    if name.contains('/') {
+        return Err(ToolkitError::InvalidInput(format!(
+            "{} name {} must not contain '/', it is kept for seed wallet children like treasury/0",
+            what, name
+        )));
+    }
+    if name.contains(':') {
+        return Err(ToolkitError::InvalidInput(format!(
+            "{} name {} must not contain ':', it is kept for prefixes like wallet: or contact:",
+            what, name
+        )));
+    }
+
+    Ok(())
+}
+
+/// Refuses to reuse a wallet name unless `force` is set, so a funded key is never
+/// silently replaced, and refuses the names `check_name` does outright.
+pub fn ensure_name_available(
+    db: &dyn WalletStore,
+    name: &str,
+    force: bool,
+) -> Result<(), ToolkitError> {
+    check_name("Wallet", name)?;
+    if !force && wallet_exists(db, name)? {
+        return Err(ToolkitError::AlreadyExists(format!(
+            "Wallet {} already exists, pass --force to overwrite it",
+            name
+        )));
+    }
+
+    Ok(())
+}
+
+/// Checks `passphrase` against a secret already in the store before sealing another one
+/// with it, so a typo cannot leave wallets of one store under different passphrases.
+/// A store without secrets takes any passphrase.
+pub fn verify_passphrase(db: &dyn WalletStore, passphrase: &str) -> Result<(), ToolkitError> {
+    let wallets = db.scan("wallet:")?.into_iter().filter_map(|(_, value)| {
+        serde_json::from_slice::<StoredWallet>(&value)
+            .ok()
+            .map(|stored| stored.secret)
+    });
+    let seeds = db
+        .scan("seed:")?
+        .into_iter()
+        .filter_map(|(_, value)| seed_record_secret(&value));
+
+    match wallets.chain(seeds).next() {
+        Some(secret) => match decrypt_secret(&secret, passphrase) {
+            Ok(_) => Ok(()),
+            Err(ToolkitError::Crypto(_)) => Err(ToolkitError::Crypto(
+                "The passphrase does not open the wallets already in the store".to_string(),
+            )),
+            Err(e) => Err(e),
+        },
+        None => Ok(()),
+    }
+}
+
+pub fn save_wallet_to_db(
+    wallet: &Wallet,
+    db: &dyn WalletStore,
+    wallet_name: &str,
+    passphrase: &str,
+    force: bool,
+    origin: Origin,
+) -> Result<(), ToolkitError> {
+    ensure_name_available(db, wallet_name, force)?;
+    verify_passphrase(db, passphrase)?;
+
+    // Encrypt the secret and serialize the stored record
+    let stored = StoredWallet::seal(wallet, WalletMeta::new(origin), passphrase)?;
+    let wallet_value = serde_json::to_vec(&stored)?;
 
     // Print debug information
-    println!("Saving wallet to DB with key: {}", get_wallet_key(wallet_name));
-    println!("Wallet value: {:?}", wallet);
+    verbose!(
+        "Saving wallet to DB with key: {}",
+        get_wallet_key(wallet_name)
+    );
+    verbose!("Wallet value: {:?}", wallet);
 
-    // Save the Wallet struct to RocksDB
-    db.put(get_wallet_key(wallet_name), wallet_value)?;
+    // Save the encrypted wallet to the store
+    let mut batch = StoreBatch::default();
+    replace_wallet_record(
+        &mut batch,
+        wallet_name,
+        &get_wallet_key(wallet_name),
+        &wallet_value,
+    );
+    db.write(batch)?;
 
     Ok(())
 }
 
-pub fn generate_keypair(db: &DB, name: &str) {
+pub fn generate_keypair(
+    db: &dyn WalletStore,
+    name: &str,
+    passphrase: &str,
+    force: bool,
+) -> Result<Wallet, ToolkitError> {
     let keypair = Keypair::new();
 
-    println!(
-        "You've generated a new Solana wallet: {}",
-        keypair.try_pubkey().unwrap().to_string()
-    );
+    let wallet = Wallet {
+        pubkey: keypair.pubkey().to_string(),
+        secret_key: keypair.to_bytes().to_vec().into(),
+    };
+
+    save_wallet_to_db(&wallet, db, name, passphrase, force, Origin::Generated)?;
+
+    Ok(wallet)
+}
+
+pub fn generate_mnemonic_keypair(
+    db: &dyn WalletStore,
+    name: &str,
+    passphrase: &str,
+    word_count: usize,
+    account: u32,
+    seed_passphrase: &str,
+    force: bool,
+) -> Result<(Wallet, Mnemonic), ToolkitError> {
+    // Fail before a seed phrase is generated that would never be saved
+    ensure_name_available(db, name, force)?;
+
+    let mnemonic = generate_mnemonic(word_count)?;
+    let keypair = keypair_from_mnemonic(&mnemonic, seed_passphrase, account)?;
 
     let wallet = Wallet {
         pubkey: keypair.pubkey().to_string(),
-        secret_key: keypair.to_bytes().to_vec(),
+        secret_key: keypair.to_bytes().to_vec().into(),
     };
 
-    save_wallet_to_db(&wallet, db, name).expect("Failed to save wallet to RocksDB");
-    println!("Wallet {} saved to RocksDB", name);
+    save_wallet_to_db(&wallet, db, name, passphrase, force, Origin::Derived)?;
+
+    Ok((wallet, mnemonic))
 }
 
-pub fn read_wallet(db: &DB, name: &str) -> Wallet {
+pub fn read_wallet(
+    db: &dyn WalletStore,
+    name: &str,
+    passphrase: &(impl PassphraseSource + ?Sized),
+) -> Result<Wallet, ToolkitError> {
     let wallet_key = get_wallet_key(name);
-    println!("Reading wallet from DB with key: {}", wallet_key); // Debug print
+    verbose!("Reading wallet from DB with key: {}", wallet_key);
 
-    let wallet_json = db
-        .get(&wallet_key)
-        .expect("Failed to read from RocksDB")
-        .expect("Wallet not found in RocksDB");
+    let wallet = match db.get(&wallet_key)? {
+        Some(wallet_json) => {
+            let stored: StoredWallet = serde_json::from_slice(&wallet_json)?;
+            stored.open(&passphrase.passphrase()?)?
+        }
+        None if watch_wallet_exists(db, name)? => {
+            return Err(ToolkitError::InvalidInput(format!(
+                "Wallet {} is watch-only and has no secret key",
+                name
+            )));
+        }
+        // Not a plain wallet, it may be a child account of a seed wallet
+        None => {
+            let child = match parse_child_name(name) {
+                Some((parent, index)) => read_child_wallet(db, parent, index, passphrase)?,
+                None => None,
+            };
+            child.ok_or_else(|| ToolkitError::NotFound(format!("Wallet {} not found", name)))?
+        }
+    };
 
-    let wallet: Wallet = serde_json::from_slice(&wallet_json).expect("Failed to deserialize wallet");
+    verbose!("You've read a Solana wallet: {} from db", wallet.pubkey);
 
-    println!("You've read a Solana wallet: {} from db", wallet.pubkey);
+    Ok(wallet)
+}
 
-    wallet
+/// The pubkey of a plain wallet, read without decrypting its secret.
+pub fn read_wallet_pubkey(
+    db: &dyn WalletStore,
+    name: &str,
+) -> Result<Option<String>, ToolkitError> {
+    match db.get(&get_wallet_key(name))? {
+        Some(value) => {
+            let stored: StoredWallet = serde_json::from_slice(&value)?;
+            Ok(Some(stored.pubkey))
+        }
+        None => Ok(None),
+    }
 }
 
-pub fn list_wallets(db: &DB) -> HashMap<String, Wallet> {
-    let mut wallets = HashMap::new();
-    let prefix = b"wallet:";
+/// Every wallet with its secret, keyed by record key. Seed wallets have no keypair of
+/// their own, only their derived children are listed. Records that cannot be decoded
+/// or decrypted are reported and skipped; a failing store is an error.
+pub fn list_wallets(
+    db: &dyn WalletStore,
+    passphrase: &str,
+) -> Result<BTreeMap<String, Wallet>, ToolkitError> {
+    let mut wallets = BTreeMap::new();
 
-    for item in db.prefix_iterator(prefix) {
-        match item {
-            Ok((key, value)) => {
-                let key_str = String::from_utf8(key.to_vec()).unwrap();
-                let wallet: Wallet = serde_json::from_slice(&value).unwrap();
-                wallets.insert(key_str, wallet);
+    for (key, value) in db.scan("wallet:")? {
+        let stored: StoredWallet = match serde_json::from_slice(&value) {
+            Ok(stored) => stored,
+            Err(e) => {
+                eprintln!("Skipping undecodable {} ({}), run `db check`", key, e);
+                continue;
+            }
+        };
+        match stored.open(passphrase) {
+            Ok(wallet) => {
+                wallets.insert(key, wallet);
             }
+            Err(e) => eprintln!("Could not decrypt {}: {}", key, e),
+        }
+    }
+
+    // Seed wallet children sort right under their parent's name
+    for (name, wallet, _) in list_child_wallets(db, passphrase)? {
+        wallets.insert(get_wallet_key(&name), wallet);
+    }
+
+    Ok(wallets)
+}
+
+/// What a listed wallet is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum WalletKind {
+    /// Keypair with its own secret
+    Plain,
+    /// BIP39 seed that children are derived from
+    Seed,
+    /// Account derived from a seed wallet
+    #[value(skip)]
+    Child,
+    /// Public key only
+    WatchOnly,
+}
+
+impl WalletKind {
+    /// The key a wallet of this kind is found under. A seed child has no record of its
+    /// own, it is addressed like a plain wallet.
+    pub fn record_key(self, name: &str) -> String {
+        match self {
+            WalletKind::Plain | WalletKind::Child => get_wallet_key(name),
+            WalletKind::Seed => get_seed_key(name),
+            WalletKind::WatchOnly => get_watch_key(name),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            WalletKind::Plain => "plain",
+            WalletKind::Seed => "seed",
+            WalletKind::Child => "child",
+            WalletKind::WatchOnly => "watch-only",
+        }
+    }
+
+    /// The kind and name of a record key: `wallet:`, `seed:` or `watch:` and the name.
+    fn of_record_key(record_key: &str) -> Option<(WalletKind, &str)> {
+        let (prefix, name) = record_key.split_once(':')?;
+        let kind = match prefix {
+            "wallet" => WalletKind::Plain,
+            "seed" => WalletKind::Seed,
+            "watch" => WalletKind::WatchOnly,
+            _ => return None,
+        };
+
+        Some((kind, name))
+    }
+}
+
+/// A listed wallet: everything but the secret.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalletEntry {
+    pub name: String,
+    pub kind: WalletKind,
+    /// `None` for a seed wallet, which only has the pubkeys of its children
+    pub pubkey: Option<String>,
+    pub meta: WalletMeta,
+}
+
+/// Lists the wallets matching `filter`, sorted by `sort` and then by name. The children
+/// of a seed wallet follow it directly, by index, whatever the sort key.
+/// Plain wallets are listed without decrypting them; seed children need the passphrase
+/// to re-derive their pubkeys.
+pub fn list_wallet_entries(
+    db: &dyn WalletStore,
+    passphrase: &(impl PassphraseSource + ?Sized),
+    filter: &WalletFilter,
+    sort: SortKey,
+) -> Result<Vec<WalletEntry>, ToolkitError> {
+    let mut wallets = Vec::new();
+    let prefix = "wallet:";
+
+    for (key, value) in db.scan(prefix)? {
+        let name = key[prefix.len()..].to_string();
+        let stored: StoredWallet = match serde_json::from_slice(&value) {
+            Ok(stored) => stored,
             Err(e) => {
-                eprintln!("Error reading from RocksDB: {:?}", e);
+                eprintln!(
+                    "Skipping undecodable wallet {} ({}), run `db check`",
+                    name, e
+                );
+                continue;
             }
+        };
+        wallets.push(WalletEntry {
+            name,
+            kind: WalletKind::Plain,
+            pubkey: Some(stored.pubkey),
+            meta: stored.meta,
+        });
+    }
+
+    for (name, meta) in list_seed_wallets(db)? {
+        wallets.push(WalletEntry {
+            name,
+            kind: WalletKind::Seed,
+            pubkey: None,
+            meta,
+        });
+    }
+
+    for (name, pubkey, meta) in list_watch_wallets(db)? {
+        wallets.push(WalletEntry {
+            name,
+            kind: WalletKind::WatchOnly,
+            pubkey: Some(pubkey),
+            meta,
+        });
+    }
+
+    // Children come in index order and are grouped under their seed wallet
+    let mut children: BTreeMap<String, Vec<WalletEntry>> = BTreeMap::new();
+    for (name, wallet, meta) in list_child_wallets(db, passphrase)? {
+        let parent = match parse_child_name(&name) {
+            Some((parent, _)) => parent.to_string(),
+            None => continue,
+        };
+        children.entry(parent).or_default().push(WalletEntry {
+            name,
+            kind: WalletKind::Child,
+            pubkey: Some(wallet.pubkey),
+            meta,
+        });
+    }
+
+    let by_key = |a: &WalletEntry, b: &WalletEntry| match sort {
+        SortKey::Name => std::cmp::Ordering::Equal,
+        SortKey::Created => a.meta.created_at.cmp(&b.meta.created_at),
+        SortKey::Origin => a.meta.origin.cmp(&b.meta.origin),
+    };
+    wallets.sort_by(|a, b| by_key(a, b).then_with(|| a.name.cmp(&b.name)));
+
+    let mut entries = Vec::new();
+    for wallet in wallets {
+        let seed_children = match wallet.kind {
+            WalletKind::Seed => children.remove(&wallet.name),
+            _ => None,
+        };
+        entries.push(wallet);
+        if let Some(mut seed_children) = seed_children {
+            // A stable sort keeps the index order among equal keys
+            seed_children.sort_by(|a, b| by_key(a, b));
+            entries.extend(seed_children);
+        }
+    }
+
+    // Filtered after ordering, so a listed child stays where its seed wallet would be
+    entries.retain(|entry| filter.matches(&entry.name, &entry.meta));
+
+    Ok(entries)
+}
+
+/// Reads the metadata of a plain wallet, a seed wallet, a seed child or a watch-only
+/// wallet without decrypting anything.
+pub fn read_wallet_meta(
+    db: &dyn WalletStore,
+    name: &str,
+) -> Result<Option<WalletMeta>, ToolkitError> {
+    if let Some(value) = db.get(&get_wallet_key(name))? {
+        let stored: StoredWallet = serde_json::from_slice(&value)?;
+        return Ok(Some(stored.meta));
+    }
+    if let Some(meta) = read_seed_meta(db, name)? {
+        return Ok(Some(meta));
+    }
+    if let Some(meta) = read_watch_meta(db, name)? {
+        return Ok(Some(meta));
+    }
+
+    match parse_child_name(name) {
+        Some((parent, index)) => read_child_meta(db, parent, index),
+        None => Ok(None),
+    }
+}
+
+/// Applies `update` to the metadata of a plain wallet, a seed wallet, a seed child or a
+/// watch-only wallet.
+pub fn update_wallet_meta(
+    db: &dyn WalletStore,
+    name: &str,
+    update: impl FnOnce(&mut WalletMeta),
+) -> Result<(), ToolkitError> {
+    let wallet_key = get_wallet_key(name);
+
+    if let Some(value) = db.get(&wallet_key)? {
+        let mut stored: StoredWallet = serde_json::from_slice(&value)?;
+        update(&mut stored.meta);
+        db.put(&wallet_key, &serde_json::to_vec(&stored)?)?;
+        return Ok(());
+    }
+    if db.get(&get_seed_key(name))?.is_some() {
+        return update_seed_meta(db, name, update);
+    }
+    if watch_wallet_exists(db, name)? {
+        update_watch_meta(db, name, update)?;
+        return Ok(());
+    }
+
+    match parse_child_name(name) {
+        Some((parent, index)) => update_child_meta(db, parent, index, update),
+        None => Err(ToolkitError::NotFound(format!("Wallet {} not found", name))),
+    }
+}
+
+/// Checks a raw wallet record: it must be a current record, and when a passphrase is
+/// given its secret must decrypt to the keypair behind its pubkey.
+pub fn validate_wallet_record(value: &[u8], passphrase: Option<&str>) -> Result<(), ToolkitError> {
+    let stored: StoredWallet = match serde_json::from_slice(value) {
+        Ok(stored) => stored,
+        Err(e) if serde_json::from_slice::<Wallet>(value).is_ok() => {
+            return Err(ToolkitError::Decode(format!(
+                "unencrypted legacy record, run `db migrate` ({})",
+                e
+            )));
+        }
+        Err(e) => {
+            return Err(ToolkitError::Decode(format!(
+                "undecodable wallet record: {}",
+                e
+            )))
+        }
+    };
+
+    if stored.version < WALLET_RECORD_VERSION {
+        return Err(ToolkitError::Decode(format!(
+            "record version {} is outdated, run `db migrate`",
+            stored.version
+        )));
+    }
+    if stored.version > WALLET_RECORD_VERSION {
+        return Err(ToolkitError::Decode(format!(
+            "record version {} was written by a newer toolkit",
+            stored.version
+        )));
+    }
+
+    if let Some(passphrase) = passphrase {
+        let pubkey = stored.pubkey.clone();
+        let wallet = stored.open(passphrase)?;
+        if wallet.keypair()?.pubkey().to_string() != pubkey {
+            return Err(ToolkitError::Crypto(
+                "secret key does not match the stored pubkey".to_string(),
+            ));
         }
     }
 
-    wallets
+    Ok(())
+}
+
+/// Whether a raw wallet record is a plaintext `{ pubkey, secret_key }` record from schema 0.
+pub fn is_plaintext_record(value: &[u8]) -> bool {
+    serde_json::from_slice::<StoredWallet>(value).is_err()
+        && serde_json::from_slice::<Wallet>(value).is_ok()
 }
 
+/// Migration to schema 1: encrypts the plaintext `{ pubkey, secret_key }` records
+/// written before secrets were encrypted at rest. Returns `None` when there is
+/// nothing to upgrade, and fails on a plaintext record when no passphrase was given.
+pub fn encrypt_plaintext_record(
+    value: &[u8],
+    passphrase: Option<&str>,
+) -> Result<Option<Vec<u8>>, ToolkitError> {
+    if serde_json::from_slice::<StoredWallet>(value).is_ok() {
+        return Ok(None);
+    }
+
+    let wallet: Wallet = serde_json::from_slice(value)?;
+    let passphrase = passphrase.ok_or_else(|| {
+        ToolkitError::InvalidInput("plaintext secret, run `db migrate` to encrypt it".to_string())
+    })?;
+    let stored = StoredWallet::seal(&wallet, WalletMeta::default(), passphrase)?;
 
-pub fn base58_to_wallet(pkey: &str) -> Result<Wallet, Box<dyn Error>> {
+    Ok(Some(serde_json::to_vec(&stored)?))
+}
+
+/// Migration to schema 2: stamps the record version and adds empty metadata.
+pub fn add_record_metadata(value: &[u8]) -> Result<Option<Vec<u8>>, ToolkitError> {
+    let mut stored: StoredWallet = serde_json::from_slice(value)?;
+    if stored.version >= WALLET_RECORD_VERSION {
+        return Ok(None);
+    }
+
+    stored.version = WALLET_RECORD_VERSION;
+
+    Ok(Some(serde_json::to_vec(&stored)?))
+}
+
+/// Finds the record key behind `name`: a plain, seed or watch-only wallet.
+fn find_record_key(db: &dyn WalletStore, name: &str) -> Result<String, ToolkitError> {
+    for key in record_keys(name) {
+        if db.get(&key)?.is_some() {
+            return Ok(key);
+        }
+    }
+
+    if parse_child_name(name).is_some() {
+        return Err(ToolkitError::InvalidInput(format!(
+            "{} is derived from a seed wallet, manage the seed wallet instead",
+            name
+        )));
+    }
+
+    Err(ToolkitError::NotFound(format!("Wallet {} not found", name)))
+}
+
+/// The record key for `name` of the same kind (plain, seed or watch-only) as `record_key`.
+fn sibling_record_key(record_key: &str, name: &str) -> String {
+    if record_key.starts_with("seed:") {
+        get_seed_key(name)
+    } else if record_key.starts_with("watch:") {
+        get_watch_key(name)
+    } else {
+        get_wallet_key(name)
+    }
+}
+
+/// Moves a wallet to the trash, where it can be restored until it is purged. Wallets
+/// removed earlier under the same name stay in the trash next to it.
+pub fn remove_wallet(db: &dyn WalletStore, name: &str) -> Result<(), ToolkitError> {
+    let record_key = find_record_key(db, name)?;
+
+    // Two removals within a second still get a key each
+    let mut removed_at = now();
+    while db.get(&get_trash_key(&record_key, removed_at))?.is_some() {
+        removed_at += 1;
+    }
+    let trash_key = get_trash_key(&record_key, removed_at);
+
+    let value = db
+        .get(&record_key)?
+        .ok_or_else(|| ToolkitError::Storage("Wallet disappeared while removing it".to_string()))?;
+
+    let mut batch = StoreBatch::default();
+    batch.put(&trash_key, value);
+    batch.delete(&record_key);
+    db.write(batch)?;
+
+    Ok(())
+}
+
+/// A wallet in the trash.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrashEntry {
+    pub kind: WalletKind,
+    pub name: String,
+    /// Unix timestamp in seconds of the removal, 0 when unknown
+    pub removed_at: u64,
+    key: String,
+    record_key: String,
+}
+
+/// The trash entries called `name`, of `kind` when given. A name trashed as several
+/// kinds of wallet is refused without a kind, rather than acting on whichever comes first.
+fn find_trashed(
+    db: &dyn WalletStore,
+    name: &str,
+    kind: Option<WalletKind>,
+) -> Result<Vec<TrashEntry>, ToolkitError> {
+    let trashed: Vec<TrashEntry> = list_trash(db)?
+        .into_iter()
+        .filter(|trashed| trashed.name == name && kind.is_none_or(|kind| trashed.kind == kind))
+        .collect();
+
+    let Some(first) = trashed.first() else {
+        return Err(ToolkitError::NotFound(format!(
+            "Wallet {} not found in the trash",
+            name
+        )));
+    };
+    if let Some(other) = trashed.iter().find(|trashed| trashed.kind != first.kind) {
+        return Err(ToolkitError::InvalidInput(format!(
+            "Wallet {} is in the trash as a {} and a {} wallet, pass --kind",
+            name,
+            first.kind.label(),
+            other.kind.label()
+        )));
+    }
+
+    Ok(trashed)
+}
+
+/// Brings the most recently removed wallet called `name`, of `kind` when given, back
+/// from the trash.
+pub fn restore_wallet(
+    db: &dyn WalletStore,
+    name: &str,
+    kind: Option<WalletKind>,
+    force: bool,
+) -> Result<(), ToolkitError> {
+    ensure_name_available(db, name, force)?;
+
+    let trashed = find_trashed(db, name, kind)?
+        .into_iter()
+        .max_by_key(|trashed| trashed.removed_at)
+        .ok_or_else(|| ToolkitError::NotFound(format!("Wallet {} not found in the trash", name)))?;
+    let value = db
+        .get(&trashed.key)?
+        .ok_or_else(|| ToolkitError::Storage("Wallet disappeared while restoring it".to_string()))?;
+
+    let mut batch = StoreBatch::default();
+    replace_wallet_record(&mut batch, name, &trashed.record_key, &value);
+    batch.delete(&trashed.key);
+    db.write(batch)
+}
+
+/// Every wallet currently in the trash, the most recently removed last.
+pub fn list_trash(db: &dyn WalletStore) -> Result<Vec<TrashEntry>, ToolkitError> {
+    let mut entries = Vec::new();
+
+    for (key, _) in db.scan("trash:")? {
+        // trash:wallet:<name>:<removed_at>, likewise for seed: and watch: records
+        let (record_key, removed_at) = match parse_trash_key(&key) {
+            Some(parsed) => parsed,
+            None => continue,
+        };
+        let (kind, name) = match WalletKind::of_record_key(&record_key) {
+            Some((kind, name)) => (kind, name.to_string()),
+            None => continue,
+        };
+        entries.push(TrashEntry {
+            kind,
+            name,
+            removed_at,
+            key,
+            record_key,
+        });
+    }
+    entries.sort_by(|a, b| {
+        a.removed_at
+            .cmp(&b.removed_at)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    Ok(entries)
+}
+
+/// Permanently deletes every removed wallet called `name`, of `kind` when given, from
+/// the trash. Without a name, every removed wallet of `kind`, or the whole trash.
+pub fn purge_trash(
+    db: &dyn WalletStore,
+    name: Option<&str>,
+    kind: Option<WalletKind>,
+) -> Result<Vec<TrashEntry>, ToolkitError> {
+    let purged: Vec<TrashEntry> = match name {
+        Some(name) => find_trashed(db, name, kind)?,
+        None => list_trash(db)?
+            .into_iter()
+            .filter(|trashed| kind.is_none_or(|kind| trashed.kind == kind))
+            .collect(),
+    };
+
+    // Every entry has a key of its own, so they all go in one batch
+    let mut batch = StoreBatch::default();
+    for trashed in &purged {
+        batch.delete(&trashed.key);
+    }
+    db.write(batch)?;
+
+    Ok(purged)
+}
+
+pub fn rename_wallet(
+    db: &dyn WalletStore,
+    from: &str,
+    to: &str,
+    force: bool,
+) -> Result<(), ToolkitError> {
+    let record_key = find_record_key(db, from)?;
+    if from == to {
+        return Ok(());
+    }
+    ensure_name_available(db, to, force)?;
+
+    let value = db
+        .get(&record_key)?
+        .ok_or_else(|| ToolkitError::Storage("Wallet disappeared while renaming it".to_string()))?;
+
+    let mut batch = StoreBatch::default();
+    replace_wallet_record(&mut batch, to, &sibling_record_key(&record_key, to), &value);
+    batch.delete(&record_key);
+    db.write(batch)?;
+
+    Ok(())
+}
+
+pub fn copy_wallet(
+    db: &dyn WalletStore,
+    from: &str,
+    to: &str,
+    force: bool,
+) -> Result<(), ToolkitError> {
+    let record_key = find_record_key(db, from)?;
+    ensure_name_available(db, to, force)?;
+
+    let value = db
+        .get(&record_key)?
+        .ok_or_else(|| ToolkitError::Storage("Wallet disappeared while copying it".to_string()))?;
+
+    let mut batch = StoreBatch::default();
+    replace_wallet_record(&mut batch, to, &sibling_record_key(&record_key, to), &value);
+    db.write(batch)?;
+
+    Ok(())
+}
+
+pub fn base58_to_wallet(pkey: &str) -> Result<Wallet, ToolkitError> {
     // Decode the base58 string into a byte array
     let decoded_secret_key = bs58::decode(pkey).into_vec()?;
 
     // Create a Keypair from the secret key
-    let keypair = Keypair::from_bytes(&decoded_secret_key)?;
+    let keypair = Keypair::from_bytes(&decoded_secret_key)
+        .map_err(|e| ToolkitError::Decode(format!("Invalid secret key: {}", e)))?;
 
     // Get the public key from the keypair
     let public_key = keypair.pubkey();
@@ -96,59 +880,429 @@ pub fn base58_to_wallet(pkey: &str) -> Result<Wallet, Box<dyn Error>> {
     // Create the Wallet struct
     let wallet = Wallet {
         pubkey: public_key.to_string(),
-        secret_key: decoded_secret_key,
+        secret_key: decoded_secret_key.into(),
     };
 
     Ok(wallet)
 }
 
-pub fn wallet_to_base58(wallet: Wallet) -> String {
-    // Convert the secret key to base58
-    let base58 = bs58::encode(wallet.secret_key).into_string();
+pub fn mnemonic_to_wallet(
+    phrase: &str,
+    seed_passphrase: &str,
+    account: u32,
+) -> Result<Wallet, ToolkitError> {
+    // Parse the seed phrase and derive the account along the Solana BIP44 path
+    let mnemonic = parse_mnemonic(phrase)?;
+    let keypair = keypair_from_mnemonic(&mnemonic, seed_passphrase, account)?;
 
-    println!("Your private key in base58 is: {:?}", base58);
+    // Create the Wallet struct
+    let wallet = Wallet {
+        pubkey: keypair.pubkey().to_string(),
+        secret_key: keypair.to_bytes().to_vec().into(),
+    };
 
-    base58
+    Ok(wallet)
 }
 
+/// Reveals the secret key in clear, only for commands that were asked to with `--reveal`.
+pub fn wallet_to_base58(wallet: Wallet) -> String {
+    // Convert the secret key to base58
+    bs58::encode(wallet.secret_key).into_string()
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tempdir::TempDir;
+    use crate::utils::seed::{derive_child_wallet, import_seed_wallet};
+    use crate::utils::store::MemoryStore;
+
+    const PASSPHRASE: &str = "test passphrase";
 
     #[test]
     fn test_generate_and_read_wallet() {
-        let tmp_dir = TempDir::new("test_db").unwrap();
-        let db = DB::open_default(tmp_dir.path()).unwrap();
+        let db = MemoryStore::default();
 
         let wallet_name = "test_wallet";
-        generate_keypair(&db, wallet_name);
+        generate_keypair(&db, wallet_name, PASSPHRASE, false).unwrap();
 
-        let wallet = read_wallet(&db, wallet_name);
+        let wallet = read_wallet(&db, wallet_name, PASSPHRASE).unwrap();
 
         assert_eq!(wallet.pubkey.len(), 44); // Length of a base58-encoded Solana pubkey
         assert_eq!(wallet.secret_key.len(), 64); // Length of a Solana secret key
+
+        // Printing a wallet never shows its secret
+        assert_eq!(wallet.to_string(), wallet.pubkey);
+        let debug = format!("{:?}", wallet);
+        assert!(debug.contains("<redacted 64 bytes>"));
+    }
+
+    #[test]
+    fn test_read_wallet_errors() {
+        let db = MemoryStore::default();
+
+        generate_keypair(&db, "funded", PASSPHRASE, false).unwrap();
+
+        assert!(matches!(
+            read_wallet(&db, "missing", PASSPHRASE),
+            Err(ToolkitError::NotFound(_))
+        ));
+        assert!(matches!(
+            read_wallet(&db, "funded", "wrong passphrase"),
+            Err(ToolkitError::Crypto(_))
+        ));
+        assert!(matches!(
+            generate_keypair(&db, "funded", PASSPHRASE, false),
+            Err(ToolkitError::AlreadyExists(_))
+        ));
+    }
+
+    #[test]
+    fn test_save_checks_the_passphrase() {
+        let db = MemoryStore::default();
+
+        // An empty store takes any passphrase
+        generate_keypair(&db, "first", PASSPHRASE, false).unwrap();
+        assert!(matches!(
+            generate_keypair(&db, "typo", "test passphrsae", false),
+            Err(ToolkitError::Crypto(_))
+        ));
+        assert!(!wallet_exists(&db, "typo").unwrap());
+
+        let phrase = generate_mnemonic(12).unwrap().to_string();
+        assert!(import_seed_wallet(&db, &phrase, "seed", "typo", "", false).is_err());
+        import_seed_wallet(&db, &phrase, "seed", PASSPHRASE, "", false).unwrap();
+        generate_keypair(&db, "second", PASSPHRASE, false).unwrap();
+    }
+
+    /// Stands in for a prompt that must not show up.
+    struct NoPrompt;
+
+    impl PassphraseSource for NoPrompt {
+        fn passphrase(&self) -> Result<String, ToolkitError> {
+            Err(ToolkitError::Cancelled(
+                "asked for the passphrase".to_string(),
+            ))
+        }
+    }
+
+    #[test]
+    fn test_listing_plain_wallets_needs_no_passphrase() {
+        let db = MemoryStore::default();
+
+        generate_keypair(&db, "payer", PASSPHRASE, false).unwrap();
+        let entries =
+            list_wallet_entries(&db, &NoPrompt, &WalletFilter::default(), SortKey::Name).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert!(read_wallet(&db, "payer", &NoPrompt).is_err());
+
+        // Seed children are derived, so listing them asks for it
+        let phrase = generate_mnemonic(12).unwrap().to_string();
+        import_seed_wallet(&db, &phrase, "treasury", PASSPHRASE, "", false).unwrap();
+        assert!(
+            list_wallet_entries(&db, &NoPrompt, &WalletFilter::default(), SortKey::Name).is_err()
+        );
+    }
+
+    #[test]
+    fn test_forced_overwrite_replaces_every_kind() {
+        let db = MemoryStore::default();
+
+        let phrase = generate_mnemonic(12).unwrap().to_string();
+        import_seed_wallet(&db, &phrase, "seed", PASSPHRASE, "", false).unwrap();
+        let plain = generate_keypair(&db, "plain", PASSPHRASE, false).unwrap();
+
+        // A plain wallet forced over a seed wallet
+        copy_wallet(&db, "plain", "seed", true).unwrap();
+        assert!(db.get(&get_seed_key("seed")).unwrap().is_none());
+        assert_eq!(read_wallet(&db, "seed", PASSPHRASE).unwrap(), plain);
+
+        // A seed wallet forced over a plain one
+        import_seed_wallet(&db, &phrase, "other", PASSPHRASE, "", false).unwrap();
+        rename_wallet(&db, "other", "plain", true).unwrap();
+        assert!(db.get(&get_wallet_key("plain")).unwrap().is_none());
+        assert!(db.get(&get_seed_key("plain")).unwrap().is_some());
+
+        // And a fresh wallet forced over a seed wallet
+        generate_keypair(&db, "plain", PASSPHRASE, true).unwrap();
+        assert!(db.get(&get_seed_key("plain")).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_names_cannot_shadow_seed_children() {
+        let db = MemoryStore::default();
+
+        let phrase = generate_mnemonic(12).unwrap().to_string();
+        import_seed_wallet(&db, &phrase, "treasury", PASSPHRASE, "", false).unwrap();
+        let (child, derived) = derive_child_wallet(&db, "treasury", None, PASSPHRASE).unwrap();
+
+        let error = generate_keypair(&db, &child, PASSPHRASE, true).unwrap_err();
+        assert_eq!(error.exit_code(), 12);
+        generate_keypair(&db, "payer", PASSPHRASE, false).unwrap();
+        assert!(rename_wallet(&db, "payer", &child, true).is_err());
+        assert!(copy_wallet(&db, "payer", "payer/0", false).is_err());
+        assert!(import_seed_wallet(&db, &phrase, "a/b", PASSPHRASE, "", false).is_err());
+
+        assert_eq!(read_wallet(&db, &child, PASSPHRASE).unwrap(), derived);
     }
 
     #[test]
     fn test_list_wallets() {
-        let tmp_dir = TempDir::new("test_db").unwrap();
-        let db = DB::open_default(tmp_dir.path()).unwrap();
+        let db = MemoryStore::default();
 
         let wallet1_name = "wallet1";
         let wallet2_name = "wallet2";
 
-        generate_keypair(&db, wallet1_name);
-        generate_keypair(&db, wallet2_name);
+        generate_keypair(&db, wallet1_name, PASSPHRASE, false).unwrap();
+        generate_keypair(&db, wallet2_name, PASSPHRASE, false).unwrap();
 
-        let wallets = list_wallets(&db);
+        let wallets = list_wallets(&db, PASSPHRASE).unwrap();
 
         assert_eq!(wallets.len(), 2);
         assert!(wallets.contains_key(&get_wallet_key(wallet1_name)));
         assert!(wallets.contains_key(&get_wallet_key(wallet2_name)));
     }
 
+    #[test]
+    fn test_list_and_read_seed_children() {
+        let db = MemoryStore::default();
+
+        generate_keypair(&db, "payer", PASSPHRASE, false).unwrap();
+        let phrase = generate_mnemonic(12).unwrap().to_string();
+        import_seed_wallet(&db, &phrase, "treasury", PASSPHRASE, "", false).unwrap();
+        let (child, derived) = derive_child_wallet(&db, "treasury", None, PASSPHRASE).unwrap();
+
+        let wallets = list_wallets(&db, PASSPHRASE).unwrap();
+        assert_eq!(wallets.len(), 2);
+        assert_eq!(wallets.get(&get_wallet_key("treasury/0")), Some(&derived));
+
+        assert_eq!(read_wallet(&db, &child, PASSPHRASE).unwrap(), derived);
+    }
+
+    #[test]
+    fn test_seed_wallets_are_listed_with_their_children() {
+        let db = MemoryStore::default();
+
+        let phrase = generate_mnemonic(12).unwrap().to_string();
+        import_seed_wallet(&db, &phrase, "treasury", PASSPHRASE, "", false).unwrap();
+        generate_keypair(&db, "treasury-old", PASSPHRASE, false).unwrap();
+        generate_keypair(&db, "zebra", PASSPHRASE, false).unwrap();
+
+        // A seed wallet without children is listed on its own
+        let entries =
+            list_wallet_entries(&db, PASSPHRASE, &WalletFilter::default(), SortKey::Name).unwrap();
+        let seed = entries.iter().find(|entry| entry.name == "treasury").unwrap();
+        assert_eq!(seed.kind, WalletKind::Seed);
+        assert_eq!(seed.pubkey, None);
+        assert_eq!(seed.meta.origin, Origin::Derived);
+        assert!(seed.meta.created_at > 0);
+
+        derive_child_wallet(&db, "treasury", None, PASSPHRASE).unwrap();
+        derive_child_wallet(&db, "treasury", None, PASSPHRASE).unwrap();
+        update_wallet_meta(&db, "treasury", |meta| {
+            meta.tags.insert("cold".to_string());
+        })
+        .unwrap();
+        assert!(read_wallet_meta(&db, "treasury")
+            .unwrap()
+            .unwrap()
+            .tags
+            .contains("cold"));
+
+        for sort in [SortKey::Name, SortKey::Created, SortKey::Origin] {
+            let entries =
+                list_wallet_entries(&db, PASSPHRASE, &WalletFilter::default(), sort).unwrap();
+            let names: Vec<_> = entries.iter().map(|entry| entry.name.as_str()).collect();
+            let seed = names.iter().position(|name| *name == "treasury").unwrap();
+            assert_eq!(&names[seed..seed + 3], ["treasury", "treasury/0", "treasury/1"]);
+        }
+    }
+
+    #[test]
+    fn test_save_refuses_to_overwrite() {
+        let db = MemoryStore::default();
+
+        generate_keypair(&db, "funded", PASSPHRASE, false).unwrap();
+        let funded = read_wallet(&db, "funded", PASSPHRASE).unwrap();
+        let other =
+            base58_to_wallet(&bs58::encode(Keypair::new().to_bytes()).into_string()).unwrap();
+
+        assert!(save_wallet_to_db(
+            &other,
+            &db,
+            "funded",
+            PASSPHRASE,
+            false,
+            Origin::Base58Import
+        )
+        .is_err());
+        assert_eq!(read_wallet(&db, "funded", PASSPHRASE).unwrap(), funded);
+
+        save_wallet_to_db(
+            &other,
+            &db,
+            "funded",
+            PASSPHRASE,
+            true,
+            Origin::Base58Import,
+        )
+        .unwrap();
+        assert_eq!(read_wallet(&db, "funded", PASSPHRASE).unwrap(), other);
+    }
+
+    #[test]
+    fn test_remove_restore_and_purge() {
+        let db = MemoryStore::default();
+
+        generate_keypair(&db, "old", PASSPHRASE, false).unwrap();
+        let wallet = read_wallet(&db, "old", PASSPHRASE).unwrap();
+
+        remove_wallet(&db, "old").unwrap();
+        assert!(!wallet_exists(&db, "old").unwrap());
+        let trashed = list_trash(&db).unwrap();
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(trashed[0].name, "old");
+        assert!(trashed[0].removed_at > 0);
+        assert!(list_wallets(&db, PASSPHRASE).unwrap().is_empty());
+
+        restore_wallet(&db, "old", None, false).unwrap();
+        assert_eq!(read_wallet(&db, "old", PASSPHRASE).unwrap(), wallet);
+        assert!(list_trash(&db).unwrap().is_empty());
+
+        remove_wallet(&db, "old").unwrap();
+        assert_eq!(purge_trash(&db, Some("old"), None).unwrap().len(), 1);
+        assert!(restore_wallet(&db, "old", None, false).is_err());
+        assert!(remove_wallet(&db, "missing").is_err());
+    }
+
+    #[test]
+    fn test_removing_a_name_again_keeps_the_earlier_wallet() {
+        let db = MemoryStore::default();
+
+        let first = generate_keypair(&db, "hot", PASSPHRASE, false).unwrap();
+        remove_wallet(&db, "hot").unwrap();
+        let second = generate_keypair(&db, "hot", PASSPHRASE, false).unwrap();
+        remove_wallet(&db, "hot").unwrap();
+
+        // Both generations are kept, and the latest comes back first
+        assert_eq!(list_trash(&db).unwrap().len(), 2);
+        restore_wallet(&db, "hot", None, false).unwrap();
+        assert_eq!(read_wallet(&db, "hot", PASSPHRASE).unwrap(), second);
+        rename_wallet(&db, "hot", "hot-new", false).unwrap();
+        restore_wallet(&db, "hot", None, false).unwrap();
+        assert_eq!(read_wallet(&db, "hot", PASSPHRASE).unwrap(), first);
+        assert!(list_trash(&db).unwrap().is_empty());
+
+        // Entries trashed before removals were timestamped still list and restore
+        let value = db.get(&get_wallet_key("hot")).unwrap().unwrap();
+        db.put("trash:wallet:legacy", &value).unwrap();
+        assert_eq!(list_trash(&db).unwrap()[0].name, "legacy");
+        restore_wallet(&db, "legacy", None, false).unwrap();
+        assert_eq!(read_wallet(&db, "legacy", PASSPHRASE).unwrap(), first);
+    }
+
+    #[test]
+    fn test_trashed_kinds_sharing_a_name() {
+        let db = MemoryStore::default();
+
+        let plain = generate_keypair(&db, "ops", PASSPHRASE, false).unwrap();
+        remove_wallet(&db, "ops").unwrap();
+        let phrase = generate_mnemonic(12).unwrap().to_string();
+        import_seed_wallet(&db, &phrase, "ops", PASSPHRASE, "", false).unwrap();
+        remove_wallet(&db, "ops").unwrap();
+
+        let kinds: Vec<_> = list_trash(&db)
+            .unwrap()
+            .into_iter()
+            .map(|trashed| (trashed.kind, trashed.name))
+            .collect();
+        assert_eq!(kinds.len(), 2);
+        assert!(kinds.contains(&(WalletKind::Plain, "ops".to_string())));
+        assert!(kinds.contains(&(WalletKind::Seed, "ops".to_string())));
+
+        // Without a kind the name is ambiguous, for restore and for purge
+        assert!(matches!(
+            restore_wallet(&db, "ops", None, false),
+            Err(ToolkitError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            purge_trash(&db, Some("ops"), None),
+            Err(ToolkitError::InvalidInput(_))
+        ));
+        assert_eq!(list_trash(&db).unwrap().len(), 2);
+
+        let purged = purge_trash(&db, Some("ops"), Some(WalletKind::Seed)).unwrap();
+        assert_eq!(purged.len(), 1);
+        assert_eq!(purged[0].kind, WalletKind::Seed);
+        restore_wallet(&db, "ops", None, false).unwrap();
+        assert_eq!(read_wallet(&db, "ops", PASSPHRASE).unwrap(), plain);
+    }
+
+    #[test]
+    fn test_rename_and_copy_wallet() {
+        let db = MemoryStore::default();
+
+        generate_keypair(&db, "a", PASSPHRASE, false).unwrap();
+        generate_keypair(&db, "b", PASSPHRASE, false).unwrap();
+        let wallet = read_wallet(&db, "a", PASSPHRASE).unwrap();
+
+        assert!(rename_wallet(&db, "a", "b", false).is_err());
+        rename_wallet(&db, "a", "c", false).unwrap();
+        assert!(!wallet_exists(&db, "a").unwrap());
+        assert_eq!(read_wallet(&db, "c", PASSPHRASE).unwrap(), wallet);
+
+        copy_wallet(&db, "c", "d", false).unwrap();
+        assert_eq!(read_wallet(&db, "c", PASSPHRASE).unwrap(), wallet);
+        assert_eq!(read_wallet(&db, "d", PASSPHRASE).unwrap(), wallet);
+    }
+
+    #[test]
+    fn test_wallet_metadata_filter_and_sort() {
+        let db = MemoryStore::default();
+
+        generate_keypair(&db, "payer", PASSPHRASE, false).unwrap();
+        let imported =
+            base58_to_wallet(&bs58::encode(Keypair::new().to_bytes()).into_string()).unwrap();
+        save_wallet_to_db(
+            &imported,
+            &db,
+            "authority",
+            PASSPHRASE,
+            false,
+            Origin::Base58Import,
+        )
+        .unwrap();
+
+        update_wallet_meta(&db, "authority", |meta| {
+            meta.description = Some("Program upgrade authority".to_string());
+            meta.tags.insert("mainnet".to_string());
+        })
+        .unwrap();
+
+        let meta = read_wallet_meta(&db, "authority").unwrap().unwrap();
+        assert_eq!(meta.origin, Origin::Base58Import);
+        assert!(meta.created_at > 0);
+        assert!(meta.tags.contains("mainnet"));
+
+        let all =
+            list_wallet_entries(&db, PASSPHRASE, &WalletFilter::default(), SortKey::Name).unwrap();
+        assert!(all.iter().all(|entry| entry.kind == WalletKind::Plain));
+        let names: Vec<_> = all.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, vec!["authority", "payer"]);
+
+        let by_origin =
+            list_wallet_entries(&db, PASSPHRASE, &WalletFilter::default(), SortKey::Origin)
+                .unwrap();
+        assert_eq!(by_origin[0].name, "payer");
+
+        let tagged = WalletFilter {
+            tags: vec!["mainnet".to_string()],
+            ..WalletFilter::default()
+        };
+        let entries = list_wallet_entries(&db, PASSPHRASE, &tagged, SortKey::Name).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].pubkey.as_deref(), Some(imported.pubkey.as_str()));
+    }
+
     #[test]
     fn test_base58_to_wallet() {
         // Generate a keypair for testing
@@ -168,18 +1322,39 @@ mod tests {
         assert_eq!(wallet.pubkey, keypair.pubkey().to_string());
 
         // Verify the secret key
-        assert_eq!(wallet.secret_key, keypair.to_bytes().to_vec());
+        assert_eq!(wallet.secret_key[..], keypair.to_bytes()[..]);
+    }
+
+    #[test]
+    fn test_mnemonic_keygen_and_recover() {
+        let db = MemoryStore::default();
+
+        let mnemonic = generate_mnemonic(12).unwrap();
+        let recovered = mnemonic_to_wallet(&mnemonic.to_string(), "", 0).unwrap();
+
+        save_wallet_to_db(
+            &recovered,
+            &db,
+            "recovered",
+            PASSPHRASE,
+            false,
+            Origin::Derived,
+        )
+        .unwrap();
+        let wallet = read_wallet(&db, "recovered", PASSPHRASE).unwrap();
+
+        assert_eq!(wallet, recovered);
+        assert!(mnemonic_to_wallet("abandon abandon", "", 0).is_err());
     }
 
     #[test]
     fn test_wallet_to_base58() {
-        let tmp_dir = TempDir::new("test_db").unwrap();
-        let db = DB::open_default(tmp_dir.path()).unwrap();
+        let db = MemoryStore::default();
 
         let wallet_name = "test_wallet";
-        generate_keypair(&db, wallet_name);
+        generate_keypair(&db, wallet_name, PASSPHRASE, false).unwrap();
 
-        let wallet = read_wallet(&db, wallet_name);
+        let wallet = read_wallet(&db, wallet_name, PASSPHRASE).unwrap();
 
         // Expected Base58 encoded string for the given secret_key
         let expected_base58 = bs58::encode(&wallet.secret_key).into_string();
@@ -190,4 +1365,23 @@ mod tests {
         // Assert the result matches the expected Base58 string
         assert_eq!(base58_result, expected_base58);
     }
+
+    #[test]
+    fn test_secret_is_encrypted_at_rest() {
+        let db = MemoryStore::default();
+
+        let wallet_name = "test_wallet";
+        generate_keypair(&db, wallet_name, PASSPHRASE, false).unwrap();
+
+        let wallet = read_wallet(&db, wallet_name, PASSPHRASE).unwrap();
+        let raw = db.get(&get_wallet_key(wallet_name)).unwrap().unwrap();
+
+        // The plaintext secret must not appear in the stored record
+        let plaintext = serde_json::to_vec(&wallet.secret_key).unwrap();
+        let leaked = raw.windows(plaintext.len()).any(|w| w == plaintext);
+        assert!(!leaked);
+
+        let stored: StoredWallet = serde_json::from_slice(&raw).unwrap();
+        assert!(stored.open("wrong passphrase").is_err());
+    }
 }